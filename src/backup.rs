@@ -0,0 +1,311 @@
+//! Encrypted backups of a secret key share to personal guardians.
+//!
+//! A share holder can protect their `SecretKeyShare` against loss by splitting it again among a
+//! set of guardians: The share becomes the value at `0` of a random polynomial, and each guardian
+//! receives its value at their index, encrypted to their `PublicKey`. The backup also contains the
+//! polynomial's commitment, so that each guardian can verify their sub-share, and anyone can check
+//! that the backup belongs to the holder's public key share.
+//!
+//! To recover the share, `threshold + 1` guardians decrypt their sub-shares and send them to the
+//! holder, encrypted to the holder's public key. The holder verifies them, interpolates them and
+//! verifies the result against the `PublicKeySet`. Any `threshold` guardians together learn
+//! nothing about the share.
+
+use pairing::bls12_381::Fr;
+use pairing::Field;
+use rand::Rng;
+
+use super::error::{Error, Result};
+use super::poly::{Commitment, Poly};
+use super::{
+    into_fr_plus_1, lagrange_coefficients, Ciphertext, IntoFr, PublicKey, PublicKeySet, SecretKey,
+    SecretKeyShare,
+};
+
+/// A secret key share, split among guardians and encrypted to their public keys.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ShareBackup {
+    /// The commitment to the polynomial whose value at `0` is the backed up share.
+    commit: Commitment,
+    /// The sub-shares, in the order of the guardians, encrypted to their public keys.
+    ciphertexts: Vec<Ciphertext>,
+}
+
+impl ShareBackup {
+    /// Creates a backup of the share, where any `threshold + 1` of the guardians can recover it.
+    /// The `j`-th guardian is the one with the `j`-th public key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::NotEnoughShares` if there are no more than `threshold` guardians, or an
+    /// `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn new<R: Rng>(
+        share: &SecretKeyShare,
+        guardians: &[PublicKey],
+        threshold: usize,
+        rng: &mut R,
+    ) -> Result<Self> {
+        if guardians.len() <= threshold {
+            return Err(Error::NotEnoughShares);
+        }
+        let mut poly = Poly::random(threshold, rng)?;
+        poly.coeff[0] = *(share.0).0;
+        let mut ciphertexts = Vec::with_capacity(guardians.len());
+        for (j, guardian) in guardians.iter().enumerate() {
+            let mut fr = poly.evaluate(into_fr_plus_1(j));
+            let sub_share = SecretKey::from_mut_ptr(&mut fr as *mut Fr)?;
+            ciphertexts.push(sub_share.encrypt_to(guardian));
+        }
+        let commit = poly.commitment();
+        Ok(ShareBackup {
+            commit,
+            ciphertexts,
+        })
+    }
+
+    /// Returns the threshold: `threshold + 1` guardians are needed to recover the share. Returns
+    /// `None` if the commitment is empty, which can only happen if the backup was not created by
+    /// `ShareBackup::new`.
+    pub fn threshold(&self) -> Option<usize> {
+        self.commit.coeff.len().checked_sub(1)
+    }
+
+    /// Returns the number of guardians.
+    pub fn guardian_count(&self) -> usize {
+        self.ciphertexts.len()
+    }
+
+    /// Returns the commitment to the sub-shares.
+    pub fn commitment(&self) -> &Commitment {
+        &self.commit
+    }
+
+    /// Returns the encrypted sub-share of the `j`-th guardian, if it exists.
+    pub fn ciphertext(&self, j: usize) -> Option<&Ciphertext> {
+        self.ciphertexts.get(j)
+    }
+}
+
+/// A guardian's decrypted sub-share of a backup, which needs to be sent to the share holder to
+/// recover their share.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupShare(SecretKey);
+
+impl BackupShare {
+    /// Decrypts the `j`-th guardian's sub-share of the backup, sent to the share holder, with the
+    /// holder's secret key, and verifies it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidShare` if the ciphertext can't be decrypted or the sub-share
+    /// doesn't match the backup's commitment, or an `Error::MlockFailed` if we have reached the
+    /// systems's locked memory limit.
+    pub fn decrypt<T: IntoFr>(
+        j: T,
+        backup: &ShareBackup,
+        sk: &SecretKey,
+        ct: &Ciphertext,
+    ) -> Result<Self> {
+        let sub_share = BackupShare(sk.decrypt_secret_key(ct)?);
+        if !sub_share.verify(j, backup) {
+            return Err(Error::InvalidShare);
+        }
+        Ok(sub_share)
+    }
+
+    /// Returns `true` if this is the `j`-th guardian's sub-share of the backup.
+    pub fn verify<T: IntoFr>(&self, j: T, backup: &ShareBackup) -> bool {
+        self.0.public_key().0 == backup.commit.evaluate(into_fr_plus_1(j))
+    }
+
+    /// Returns the sub-share encrypted to the share holder's public key, so that it can be sent
+    /// to them.
+    pub fn encrypt(&self, pk: &PublicKey) -> Ciphertext {
+        self.0.encrypt_to(pk)
+    }
+}
+
+impl SecretKey {
+    /// Decrypts the `j`-th guardian's sub-share of the backup, with that guardian's secret key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidShare` if the sub-share doesn't exist, can't be decrypted, or
+    /// doesn't match the backup's commitment, or an `Error::MlockFailed` if we have reached the
+    /// systems's locked memory limit.
+    pub fn decrypt_backup_share(&self, backup: &ShareBackup, j: usize) -> Result<BackupShare> {
+        let ct = backup.ciphertext(j).ok_or(Error::InvalidShare)?;
+        BackupShare::decrypt(j, backup, self, ct)
+    }
+}
+
+impl PublicKeySet {
+    /// Returns `true` if the backup belongs to the `i`-th secret key share.
+    pub fn verify_backup<T: IntoFr>(&self, i: T, backup: &ShareBackup) -> bool {
+        backup
+            .commit
+            .coeff
+            .first()
+            .filter(|c0| **c0 == (self.public_key_share(i).0).0)
+            .is_some()
+    }
+}
+
+impl SecretKeyShare {
+    /// Recovers the `i`-th secret key share from its backup, given the guardians' sub-shares by
+    /// their indices.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::NotEnoughShares` if there are no more than `threshold` sub-shares, an
+    /// `Error::DuplicateEntry` if an index appears twice, an `Error::InvalidShare` if the backup
+    /// doesn't belong to the `i`-th share or any sub-share doesn't match it, or an
+    /// `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn from_backup<'a, T, U, I>(
+        i: U,
+        pk_set: &PublicKeySet,
+        backup: &ShareBackup,
+        sub_shares: I,
+    ) -> Result<Self>
+    where
+        I: IntoIterator<Item = (T, &'a BackupShare)>,
+        T: IntoFr,
+        U: IntoFr,
+    {
+        if !pk_set.verify_backup(i, backup) {
+            return Err(Error::InvalidShare);
+        }
+        let threshold = backup.threshold().ok_or(Error::InvalidShare)?;
+        let sub_shares: Vec<_> = sub_shares.into_iter().collect();
+        if sub_shares.len() <= threshold {
+            return Err(Error::NotEnoughShares);
+        }
+        if !sub_shares.iter().all(|&(j, sub)| sub.verify(j, backup)) {
+            return Err(Error::InvalidShare);
+        }
+        let xs: Vec<Fr> = sub_shares.iter().map(|&(j, _)| into_fr_plus_1(j)).collect();
+        let mut fr = Fr::zero();
+        for ((_, sub), l0) in sub_shares.iter().zip(lagrange_coefficients(&xs)?) {
+            let mut term = *(sub.0).0;
+            term.mul_assign(&l0);
+            fr.add_assign(&term);
+        }
+        let share = SecretKeyShare::from_mut_ptr(&mut fr as *mut Fr)?;
+        if share.public_key_share() != pk_set.public_key_share(i) {
+            return Err(Error::InvalidShare);
+        }
+        Ok(share)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use bincode;
+    use rand;
+
+    use super::super::SecretKeySet;
+    use super::*;
+
+    #[test]
+    fn test_backup() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng).expect("Failed to create key set");
+        let pk_set = sk_set.public_keys();
+        let share = sk_set.secret_key_share(3).expect("Failed to create share");
+
+        // The holder of share 3 backs it up with four guardians, any three of whom can recover it.
+        let guardian_sks: Vec<SecretKey> = (0..4).map(|_| SecretKey::random()).collect();
+        let guardian_pks: Vec<PublicKey> = guardian_sks.iter().map(SecretKey::public_key).collect();
+        let backup =
+            ShareBackup::new(&share, &guardian_pks, 2, &mut rng).expect("Failed to back up");
+        assert_eq!(Some(2), backup.threshold());
+        assert_eq!(4, backup.guardian_count());
+        assert!(pk_set.verify_backup(3, &backup));
+        assert!(!pk_set.verify_backup(2, &backup));
+
+        // Each guardian decrypts their sub-share and sends it to the holder, who verifies it.
+        let holder_sk = SecretKey::random();
+        let sub_shares: BTreeMap<_, _> = guardian_sks
+            .iter()
+            .enumerate()
+            .map(|(j, sk)| {
+                let sub = sk
+                    .decrypt_backup_share(&backup, j)
+                    .expect("Failed to decrypt sub-share");
+                let msg = bincode::serialize(&sub.encrypt(&holder_sk.public_key()))
+                    .expect("serialize ciphertext");
+                let ct: Ciphertext = bincode::deserialize(&msg).expect("deserialize ciphertext");
+                let received =
+                    BackupShare::decrypt(j, &backup, &holder_sk, &ct).expect("valid sub-share");
+                assert_eq!(sub, received);
+                (j, received)
+            })
+            .collect();
+        let recovered = SecretKeyShare::from_backup(3, &pk_set, &backup, sub_shares.iter().skip(1))
+            .expect("Failed to recover");
+        assert_eq!(share, recovered);
+
+        // Two guardians are not enough, and a sub-share under the wrong index is rejected.
+        let result = SecretKeyShare::from_backup(3, &pk_set, &backup, sub_shares.iter().take(2));
+        assert_eq!(Err(Error::NotEnoughShares), result);
+        let wrong = vec![
+            (0, &sub_shares[&0]),
+            (1, &sub_shares[&2]),
+            (2, &sub_shares[&1]),
+        ];
+        let result = SecretKeyShare::from_backup(3, &pk_set, &backup, wrong);
+        assert_eq!(Err(Error::InvalidShare), result);
+
+        // The backup can't be used to recover a different share.
+        let result = SecretKeyShare::from_backup(2, &pk_set, &backup, &sub_shares);
+        assert_eq!(Err(Error::InvalidShare), result);
+    }
+
+    #[test]
+    fn test_invalid_backup() {
+        let mut rng = rand::thread_rng();
+        let share = SecretKeyShare::default();
+        let guardian_sks: Vec<SecretKey> = (0..3).map(|_| SecretKey::random()).collect();
+        let guardian_pks: Vec<PublicKey> = guardian_sks.iter().map(SecretKey::public_key).collect();
+
+        // There must be more guardians than the threshold.
+        let result = ShareBackup::new(&share, &guardian_pks, 3, &mut rng);
+        assert_eq!(Err(Error::NotEnoughShares), result);
+
+        // A guardian can't decrypt someone else's sub-share.
+        let backup =
+            ShareBackup::new(&share, &guardian_pks, 1, &mut rng).expect("Failed to back up");
+        let result = guardian_sks[0].decrypt_backup_share(&backup, 1);
+        assert_eq!(Err(Error::InvalidShare), result);
+        let result = guardian_sks[0].decrypt_backup_share(&backup, 3);
+        assert_eq!(Err(Error::InvalidShare), result);
+
+        // A sub-share sent to the holder can't be decrypted by anyone else, or accepted as
+        // another guardian's.
+        let sub = guardian_sks[1]
+            .decrypt_backup_share(&backup, 1)
+            .expect("Failed to decrypt sub-share");
+        let holder_sk = SecretKey::random();
+        let ct = sub.encrypt(&holder_sk.public_key());
+        let result = BackupShare::decrypt(1, &backup, &guardian_sks[1], &ct);
+        assert_eq!(Err(Error::InvalidShare), result);
+        let result = BackupShare::decrypt(0, &backup, &holder_sk, &ct);
+        assert_eq!(Err(Error::InvalidShare), result);
+
+        // A received backup with an empty commitment doesn't have a threshold, and can't be used.
+        let empty = ShareBackup {
+            commit: Commitment { coeff: vec![] },
+            ciphertexts: vec![],
+        };
+        let empty: ShareBackup = bincode::deserialize(&bincode::serialize(&empty).expect("ser"))
+            .expect("deserialize backup");
+        assert_eq!(None, empty.threshold());
+        let pk_set = SecretKeySet::random(0, &mut rng)
+            .expect("Failed to create key set")
+            .public_keys();
+        let result = SecretKeyShare::from_backup(0, &pk_set, &empty, vec![(0, &sub)]);
+        assert_eq!(Err(Error::InvalidShare), result);
+    }
+}
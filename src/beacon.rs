@@ -0,0 +1,525 @@
+//! A threshold randomness beacon.
+//!
+//! In every round, the beacon's committee signs a message that depends on the round number, and
+//! any `threshold + 1` signature shares combine into a unique signature. Its hash is the round's
+//! randomness: Nobody can predict it before `threshold + 1` members have revealed their shares.
+//!
+//! In _chained_ mode, the message for round `r` also contains the signature of round `r - 1` (or
+//! the genesis seed, for round `1`), so the entries form a hash chain. In _unchained_ mode, the
+//! message only depends on the round number, so shares for future rounds can be produced and
+//! verified independently of the previous rounds.
+//!
+//! A beacon can also use drand's message format and hash function; see the `drand` module.
+
+use std::collections::BTreeMap;
+
+use byteorder::{BigEndian, ByteOrder};
+use pairing::bls12_381::G2;
+use tiny_keccak::sha3_256;
+
+use super::{
+    drand, hash_g2, Error, PublicKeySet, Result, SecretKeyShare, Signature, SignatureShare,
+};
+
+/// The number of rounds after the next one for which shares are buffered. Shares for later rounds
+/// are ignored.
+const MAX_FUTURE_ROUNDS: u64 = 4;
+
+/// Whether each round's message depends on the previous round's signature.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BeaconMode {
+    /// The message for round `r` is the hash of the previous signature and `r`.
+    Chained,
+    /// The message for round `r` is the hash of `r`.
+    Unchained,
+}
+
+/// How round messages are computed and hashed to `G2`.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BeaconFormat {
+    /// This crate's format: messages are SHA3-256 hashes, signed like any other message.
+    Native,
+    /// drand's format: messages are SHA-256 hashes, hashed to `G2` with drand's ciphersuite.
+    Drand,
+}
+
+impl BeaconFormat {
+    /// Returns the message for the given round.
+    fn round_message(self, round: u64, previous_signature: Option<&[u8]>) -> Vec<u8> {
+        match self {
+            BeaconFormat::Native => round_message(round, previous_signature),
+            BeaconFormat::Drand => drand::round_message(round, previous_signature),
+        }
+    }
+
+    /// Returns the element of `G2` that is signed for the given message.
+    fn hash_g2(self, msg: &[u8]) -> G2 {
+        match self {
+            BeaconFormat::Native => hash_g2(msg),
+            BeaconFormat::Drand => drand::hash_g2(msg),
+        }
+    }
+}
+
+/// The output of one round of the beacon.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BeaconEntry {
+    /// The round number, starting at `1`.
+    pub round: u64,
+    /// The signature of the round's message, by the committee's main public key.
+    pub signature: Signature,
+    /// In chained mode, the previous round's signature, or the genesis seed if this is round `1`.
+    /// `None` in unchained mode.
+    pub previous_signature: Option<Vec<u8>>,
+}
+
+impl BeaconEntry {
+    /// Returns the message that is signed in this entry's round, in the native format.
+    pub fn message(&self) -> Vec<u8> {
+        round_message(self.round, self.previous_signature.as_deref())
+    }
+
+    /// Returns the round's randomness: the hash of the signature. For entries in drand's format,
+    /// use `drand::randomness` instead.
+    pub fn randomness(&self) -> [u8; 32] {
+        sha3_256(&self.signature.to_bytes())
+    }
+}
+
+/// A committee member's signature share for one round.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BeaconShare {
+    /// The round number.
+    pub round: u64,
+    /// The index of the signer's secret key share.
+    pub index: usize,
+    /// The signature share of the round's message.
+    pub share: SignatureShare,
+}
+
+/// The state of a randomness beacon, as seen by a committee member or an observer.
+///
+/// The beacon collects signature shares for the next few rounds and outputs a `BeaconEntry` as
+/// soon as `threshold + 1` valid shares for the next round are known. It is transport-agnostic:
+/// The user is responsible for broadcasting the `BeaconShare`s created by `sign_share`, and for
+/// passing all received shares to `handle_share`.
+#[derive(Clone, Debug)]
+pub struct Beacon {
+    /// The committee's public keys.
+    pk_set: PublicKeySet,
+    /// Whether the rounds are chained.
+    mode: BeaconMode,
+    /// The format of the round messages.
+    format: BeaconFormat,
+    /// In chained mode, the value that is used as the "previous signature" in round `1`.
+    genesis_seed: Vec<u8>,
+    /// The entry of the latest completed round.
+    latest: Option<BeaconEntry>,
+    /// The received shares for upcoming rounds, by round and signer index. Shares for the next
+    /// round have been verified; in chained mode, shares for later rounds have not. Only the
+    /// first share for each index is kept.
+    shares: BTreeMap<u64, BTreeMap<usize, SignatureShare>>,
+}
+
+impl Beacon {
+    /// Creates a new beacon that starts at round `1`. The genesis seed is only used in chained
+    /// mode.
+    pub fn new(pk_set: PublicKeySet, mode: BeaconMode, genesis_seed: Vec<u8>) -> Self {
+        Beacon::with_format(pk_set, mode, BeaconFormat::Native, genesis_seed)
+    }
+
+    /// Creates a new beacon in drand's format, that starts at round `1`. Its entries can be
+    /// verified by drand clients, given the public key set's main public key.
+    pub fn new_drand(pk_set: PublicKeySet, mode: BeaconMode, genesis_seed: Vec<u8>) -> Self {
+        Beacon::with_format(pk_set, mode, BeaconFormat::Drand, genesis_seed)
+    }
+
+    fn with_format(
+        pk_set: PublicKeySet,
+        mode: BeaconMode,
+        format: BeaconFormat,
+        genesis_seed: Vec<u8>,
+    ) -> Self {
+        Beacon {
+            pk_set,
+            mode,
+            format,
+            genesis_seed,
+            latest: None,
+            shares: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the beacon's mode.
+    pub fn mode(&self) -> BeaconMode {
+        self.mode
+    }
+
+    /// Returns the beacon's message format.
+    pub fn format(&self) -> BeaconFormat {
+        self.format
+    }
+
+    /// Returns the entry of the latest completed round, if any.
+    pub fn latest_entry(&self) -> Option<&BeaconEntry> {
+        self.latest.as_ref()
+    }
+
+    /// Returns the number of the next round that has not been completed yet.
+    pub fn next_round(&self) -> u64 {
+        self.latest.as_ref().map_or(1, |entry| entry.round + 1)
+    }
+
+    /// Returns the message for the given round. In chained mode, this is only known for the next
+    /// round, so `None` is returned for all other rounds.
+    pub fn message(&self, round: u64) -> Option<Vec<u8>> {
+        match self.mode {
+            BeaconMode::Unchained => Some(self.format.round_message(round, None)),
+            BeaconMode::Chained if round == self.next_round() => {
+                let previous_signature = self.previous_signature();
+                Some(self.format.round_message(round, Some(&previous_signature)))
+            }
+            BeaconMode::Chained => None,
+        }
+    }
+
+    /// Signs the next round's message with the given secret key share, which must belong to
+    /// index `index` in the beacon's public key set. The resulting share should be sent to all
+    /// participants, including ourselves.
+    pub fn sign_share(&self, index: usize, sk_share: &SecretKeyShare) -> BeaconShare {
+        let round = self.next_round();
+        let msg = self.message(round).expect("next round's message is known");
+        BeaconShare {
+            round,
+            index,
+            share: sk_share.sign_g2(self.format.hash_g2(&msg)),
+        }
+    }
+
+    /// Handles a signature share received from a committee member. Returns the new entries, in
+    /// order, if this share completed the next round. That can also complete the rounds after it,
+    /// if enough shares for them have been buffered.
+    ///
+    /// Shares for past rounds, for rounds more than `MAX_FUTURE_ROUNDS` after the next one, and
+    /// for an index that already has a share in the same round are ignored. In chained mode,
+    /// shares for later rounds are kept and verified once their round is next. The share's index
+    /// is not authenticated, so the transport layer should make sure it belongs to the sender.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidShare` if the share is invalid for its round.
+    pub fn handle_share(&mut self, share: BeaconShare) -> Result<Vec<BeaconEntry>> {
+        let next_round = self.next_round();
+        if share.round < next_round || share.round - next_round > MAX_FUTURE_ROUNDS {
+            return Ok(Vec::new());
+        }
+        if let Some(shares) = self.shares.get(&share.round) {
+            if shares.contains_key(&share.index) {
+                return Ok(Vec::new());
+            }
+        }
+        if let Some(msg) = self.message(share.round) {
+            let pk_share = self.pk_set.public_key_share(share.index);
+            if !pk_share.verify_g2(&share.share, self.format.hash_g2(&msg)) {
+                return Err(Error::InvalidShare);
+            }
+        }
+        self.shares
+            .entry(share.round)
+            .or_default()
+            .insert(share.index, share.share);
+        self.try_complete_rounds()
+    }
+
+    /// Handles a completed entry for the next round, e.g. received from another participant
+    /// while catching up. Returns the entries of the following rounds that could be completed
+    /// with the buffered shares.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidBeaconEntry` if the entry is not a valid entry for the next round.
+    pub fn handle_entry(&mut self, entry: BeaconEntry) -> Result<Vec<BeaconEntry>> {
+        if entry.round != self.next_round() {
+            return Err(Error::InvalidBeaconEntry);
+        }
+        if self.mode == BeaconMode::Chained
+            && entry.previous_signature.as_ref() != Some(&self.previous_signature())
+        {
+            return Err(Error::InvalidBeaconEntry);
+        }
+        if !self.verify_entry(&entry) {
+            return Err(Error::InvalidBeaconEntry);
+        }
+        self.complete_round(entry);
+        self.try_complete_rounds()
+    }
+
+    /// Returns `true` if the entry is signed by the committee and has the right format for the
+    /// beacon's mode. In chained mode, this does not check that the previous signature is the
+    /// one from the actual previous entry.
+    pub fn verify_entry(&self, entry: &BeaconEntry) -> bool {
+        let has_previous = entry.previous_signature.is_some();
+        if has_previous != (self.mode == BeaconMode::Chained) || entry.round == 0 {
+            return false;
+        }
+        let msg = self
+            .format
+            .round_message(entry.round, entry.previous_signature.as_deref());
+        self.pk_set
+            .public_key()
+            .verify_g2(&entry.signature, self.format.hash_g2(&msg))
+    }
+
+    /// Completes as many rounds as possible with the buffered shares, and returns their entries.
+    fn try_complete_rounds(&mut self) -> Result<Vec<BeaconEntry>> {
+        let mut entries = Vec::new();
+        while let Some(entry) = self.try_complete_round()? {
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+
+    /// Combines the shares for the next round, if there are enough of them.
+    fn try_complete_round(&mut self) -> Result<Option<BeaconEntry>> {
+        let round = self.next_round();
+        let signature = match self.shares.get(&round) {
+            Some(shares) if shares.len() > self.pk_set.threshold() => {
+                self.pk_set.combine_signatures(shares)?
+            }
+            _ => return Ok(None),
+        };
+        let previous_signature = match self.mode {
+            BeaconMode::Chained => Some(self.previous_signature()),
+            BeaconMode::Unchained => None,
+        };
+        let entry = BeaconEntry {
+            round,
+            signature,
+            previous_signature,
+        };
+        self.complete_round(entry.clone());
+        Ok(Some(entry))
+    }
+
+    /// Moves on to the round after the given entry's, and verifies the shares we already have
+    /// for it.
+    fn complete_round(&mut self, entry: BeaconEntry) {
+        self.latest = Some(entry);
+        let next_round = self.next_round();
+        self.shares = self.shares.split_off(&next_round);
+        if self.mode == BeaconMode::Chained {
+            let msg = self
+                .message(next_round)
+                .expect("next round's message is known");
+            let hash = self.format.hash_g2(&msg);
+            if let Some(shares) = self.shares.get_mut(&next_round) {
+                let pk_set = &self.pk_set;
+                shares.retain(|i, share| pk_set.public_key_share(*i).verify_g2(share, hash));
+            }
+        }
+    }
+
+    /// Returns the value that the next round's message has to include in chained mode.
+    fn previous_signature(&self) -> Vec<u8> {
+        match self.latest {
+            Some(ref entry) => entry.signature.to_bytes(),
+            None => self.genesis_seed.clone(),
+        }
+    }
+}
+
+/// Returns the message for the given round: the hash of the previous signature, if any, and the
+/// round number in big-endian encoding.
+fn round_message(round: u64, previous_signature: Option<&[u8]>) -> Vec<u8> {
+    let mut msg = previous_signature.unwrap_or(&[]).to_vec();
+    let mut round_bytes = [0; 8];
+    BigEndian::write_u64(&mut round_bytes, round);
+    msg.extend(&round_bytes);
+    sha3_256(&msg).to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand;
+
+    use super::super::SecretKeySet;
+    use super::*;
+
+    /// Runs a few rounds in the given mode, with each node receiving the shares in a different
+    /// order, and returns the entries.
+    fn run_beacon(mode: BeaconMode) -> Vec<BeaconEntry> {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng).expect("Failed to create `SecretKeySet`");
+        let pk_set = sk_set.public_keys();
+        let sk_shares: Vec<_> = (0..5)
+            .map(|i| {
+                sk_set
+                    .secret_key_share(i)
+                    .unwrap_or_else(|_| panic!("Failed to create `SecretKeyShare` #{}", i))
+            })
+            .collect();
+        let genesis = b"genesis".to_vec();
+        let mut beacons: Vec<_> = (0..5)
+            .map(|_| Beacon::new(pk_set.clone(), mode, genesis.clone()))
+            .collect();
+
+        let mut entries = Vec::new();
+        for round in 1..4 {
+            let shares: Vec<_> = sk_shares
+                .iter()
+                .enumerate()
+                .map(|(i, sk_share)| beacons[i].sign_share(i, sk_share))
+                .collect();
+            let mut outputs = Vec::new();
+            for (n, beacon) in beacons.iter_mut().enumerate() {
+                // Node `n` receives the shares starting with node `n`'s.
+                let mut output = None;
+                for share in shares.iter().cycle().skip(n).take(shares.len()) {
+                    for entry in beacon.handle_share(share.clone()).expect("valid share") {
+                        assert!(output.is_none());
+                        output = Some(entry);
+                    }
+                }
+                outputs.push(output.expect("round complete"));
+            }
+            // Every node computed the same entry, which can be verified by anyone.
+            for entry in &outputs {
+                assert_eq!(outputs[0], *entry);
+            }
+            let entry = outputs.swap_remove(0);
+            assert_eq!(round, entry.round);
+            assert!(beacons[0].verify_entry(&entry));
+            entries.push(entry);
+        }
+        entries
+    }
+
+    #[test]
+    fn test_chained_beacon() {
+        let entries = run_beacon(BeaconMode::Chained);
+        assert_eq!(
+            Some(&b"genesis"[..]),
+            entries[0].previous_signature.as_deref()
+        );
+        for pair in entries.windows(2) {
+            let prev_sig = pair[0].signature.to_bytes();
+            assert_eq!(Some(prev_sig), pair[1].previous_signature);
+            assert_ne!(pair[0].randomness(), pair[1].randomness());
+        }
+    }
+
+    #[test]
+    fn test_unchained_beacon() {
+        let entries = run_beacon(BeaconMode::Unchained);
+        assert!(entries
+            .iter()
+            .all(|entry| entry.previous_signature.is_none()));
+        assert_ne!(entries[0].randomness(), entries[1].randomness());
+    }
+
+    #[test]
+    fn test_invalid_shares_and_entries() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng).expect("Failed to create `SecretKeySet`");
+        let pk_set = sk_set.public_keys();
+        let sk_share = |i: usize| {
+            sk_set
+                .secret_key_share(i)
+                .unwrap_or_else(|_| panic!("Failed to create `SecretKeyShare` #{}", i))
+        };
+        let mut beacon = Beacon::new(pk_set.clone(), BeaconMode::Chained, vec![]);
+        let mut observer = Beacon::new(pk_set.clone(), BeaconMode::Chained, vec![]);
+
+        // A share claiming the wrong index is rejected.
+        let mut share = beacon.sign_share(0, &sk_share(0));
+        share.index = 1;
+        assert_eq!(Err(Error::InvalidShare), beacon.handle_share(share));
+
+        let share0 = beacon.sign_share(0, &sk_share(0));
+        let share1 = beacon.sign_share(1, &sk_share(1));
+        assert_eq!(Ok(vec![]), beacon.handle_share(share0.clone()));
+        let entry = beacon
+            .handle_share(share1)
+            .expect("valid share")
+            .pop()
+            .expect("round complete");
+        // Stale shares are ignored.
+        assert_eq!(Ok(vec![]), beacon.handle_share(share0));
+
+        // An observer can catch up using the entry, but not with a tampered one.
+        let mut wrong_entry = entry.clone();
+        wrong_entry.previous_signature = Some(b"wrong".to_vec());
+        assert_eq!(
+            Err(Error::InvalidBeaconEntry),
+            observer.handle_entry(wrong_entry)
+        );
+        assert_eq!(Ok(vec![]), observer.handle_entry(entry.clone()));
+        assert_eq!(Some(&entry), observer.latest_entry());
+        assert_eq!(2, observer.next_round());
+    }
+
+    #[test]
+    fn test_buffered_shares() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng).expect("Failed to create `SecretKeySet`");
+        let pk_set = sk_set.public_keys();
+        let sk_shares: Vec<_> = (0..3)
+            .map(|i| {
+                sk_set
+                    .secret_key_share(i)
+                    .unwrap_or_else(|_| panic!("Failed to create `SecretKeyShare` #{}", i))
+            })
+            .collect();
+        for &mode in &[BeaconMode::Chained, BeaconMode::Unchained] {
+            // The leader completes round 1 and signs round 2 before the late node has round 1.
+            let mut leader = Beacon::new(pk_set.clone(), mode, b"genesis".to_vec());
+            let mut late = Beacon::new(pk_set.clone(), mode, b"genesis".to_vec());
+            let shares1: Vec<_> = (0..2)
+                .map(|i| leader.sign_share(i, &sk_shares[i]))
+                .collect();
+            assert_eq!(Ok(vec![]), leader.handle_share(shares1[0].clone()));
+            let entry1 = leader
+                .handle_share(shares1[1].clone())
+                .expect("valid share")
+                .pop()
+                .expect("round complete");
+            let shares2: Vec<_> = (0..2)
+                .map(|i| leader.sign_share(i, &sk_shares[i]))
+                .collect();
+            let entry2 = leader
+                .handle_share(shares2[0].clone())
+                .and_then(|_| leader.handle_share(shares2[1].clone()))
+                .expect("valid share")
+                .pop()
+                .expect("round complete");
+
+            // The late node buffers round 2, and completes it together with round 1. A second
+            // share for the same index doesn't replace the first one.
+            for share in &shares2 {
+                assert_eq!(Ok(vec![]), late.handle_share(share.clone()));
+            }
+            let mut duplicate = shares2[0].clone();
+            duplicate.share = shares1[0].share.clone();
+            assert_eq!(Ok(vec![]), late.handle_share(duplicate));
+            assert_eq!(Ok(vec![]), late.handle_share(shares1[0].clone()));
+            assert_eq!(
+                Ok(vec![entry1.clone(), entry2.clone()]),
+                late.handle_share(shares1[1].clone())
+            );
+            assert_eq!(3, late.next_round());
+
+            // An observer catching up with an entry also completes the buffered round.
+            let mut observer = Beacon::new(pk_set.clone(), mode, b"genesis".to_vec());
+            for share in &shares2 {
+                assert_eq!(Ok(vec![]), observer.handle_share(share.clone()));
+            }
+            assert_eq!(Ok(vec![entry2]), observer.handle_entry(entry1));
+
+            // Shares too far in the future are ignored.
+            let mut far = shares2[0].clone();
+            far.round = observer.next_round() + MAX_FUTURE_ROUNDS + 1;
+            assert_eq!(Ok(vec![]), observer.handle_share(far.clone()));
+            assert!(!observer.shares.contains_key(&far.round));
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Blind BLS signatures.
+//!
+//! To obtain a signature without revealing the message to the signer, the user multiplies the
+//! message's hash `hash_g2(msg)` by a random _blinding factor_ `r`, and sends the result to the
+//! signer. The signature `s * r * hash_g2(msg)` of the blinded message, multiplied by `1 / r`, is
+//! the ordinary signature `s * hash_g2(msg)`, which can be verified with `PublicKey::verify`.
+//!
+//! This works with signature shares, too: The user unblinds each share with the same factor, and
+//! combines the unblinded shares with `PublicKeySet::combine_signatures`.
+
+use std::fmt;
+
+use pairing::bls12_381::{Fr, G2Affine, G2};
+use pairing::{CurveAffine, CurveProjective, Field};
+use rand::Rng;
+
+use super::{hash_g2, serde_impl, Result, SecretKey, Signature, SignatureShare};
+
+/// A message hash in `G2`, multiplied by a blinding factor. It can be signed using
+/// `SecretKey::sign_g2` or `SecretKeyShare::sign_g2`, and the result can be verified using
+/// `PublicKey::verify_g2` or `PublicKeyShare::verify_g2`.
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct BlindedMessage(#[serde(with = "serde_impl::projective")] G2);
+
+impl From<BlindedMessage> for G2Affine {
+    fn from(blinded: BlindedMessage) -> G2Affine {
+        blinded.0.into_affine()
+    }
+}
+
+/// A secret blinding factor `r`, together with its inverse. Like a `SecretKey`, it is kept in
+/// locked memory and overwritten with zeros when dropped.
+pub struct BlindingFactor {
+    /// The factor `r`.
+    factor: SecretKey,
+    /// The inverse `1 / r`.
+    inverse: SecretKey,
+}
+
+/// A debug statement where the blinding factor is redacted.
+impl fmt::Debug for BlindingFactor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BlindingFactor(...)")
+    }
+}
+
+impl BlindingFactor {
+    /// Creates a random non-zero blinding factor.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn random<R: Rng>(rng: &mut R) -> Result<Self> {
+        let mut fr: Fr = rng.gen();
+        while fr.is_zero() {
+            fr = rng.gen();
+        }
+        let mut inv = fr.inverse().expect("factor is non-zero");
+        let factor = SecretKey::from_mut_ptr(&mut fr as *mut Fr)?;
+        let inverse = SecretKey::from_mut_ptr(&mut inv as *mut Fr)?;
+        Ok(BlindingFactor { factor, inverse })
+    }
+
+    /// Returns the hash of the message, blinded with this factor.
+    pub fn blind<M: AsRef<[u8]>>(&self, msg: M) -> BlindedMessage {
+        BlindedMessage(hash_g2(msg).into_affine().mul(*self.factor.0))
+    }
+
+    /// Returns the signature of the original message, given the signature of the blinded one.
+    pub fn unblind(&self, sig: &Signature) -> Signature {
+        Signature(sig.0.into_affine().mul(*self.inverse.0))
+    }
+
+    /// Returns the signature share for the original message, given the share for the blinded
+    /// one.
+    pub fn unblind_share(&self, share: &SignatureShare) -> SignatureShare {
+        SignatureShare(self.unblind(&share.0))
+    }
+}
+
+/// Creates a random blinding factor and returns the blinded message together with the factor.
+///
+/// # Errors
+///
+/// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+pub fn blind<M: AsRef<[u8]>, R: Rng>(
+    msg: M,
+    rng: &mut R,
+) -> Result<(BlindedMessage, BlindingFactor)> {
+    let factor = BlindingFactor::random(rng)?;
+    Ok((factor.blind(msg), factor))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rand::{self, random};
+
+    use super::super::SecretKeySet;
+    use super::*;
+
+    #[test]
+    fn test_simple_blind_sig() {
+        let mut rng = rand::thread_rng();
+        let sk: SecretKey = random();
+        let pk = sk.public_key();
+        let msg = b"Anonymous token #1234";
+
+        let (blinded, factor) = blind(msg, &mut rng).expect("Failed to blind message");
+        // The signer only sees the blinded message, which looks unrelated to the message.
+        assert_ne!(blinded, factor.blind(b"Anonymous token #1235"));
+        let blind_sig = sk.sign_g2(blinded);
+        assert!(pk.verify_g2(&blind_sig, blinded));
+        assert!(!pk.verify(&blind_sig, msg));
+
+        // The unblinded signature is the ordinary signature of the message.
+        let sig = factor.unblind(&blind_sig);
+        assert!(pk.verify(&sig, msg));
+        assert_eq!(sk.sign(msg), sig);
+
+        // Another blinding factor gives a different blinded message, but the same signature.
+        let (blinded2, factor2) = blind(msg, &mut rng).expect("Failed to blind message");
+        assert_ne!(blinded, blinded2);
+        assert_eq!(sig, factor2.unblind(&sk.sign_g2(blinded2)));
+    }
+
+    #[test]
+    fn test_threshold_blind_sig() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng).expect("Failed to create `SecretKeySet`");
+        let pk_set = sk_set.public_keys();
+        let msg = b"Anonymous token #5678";
+        let (blinded, factor) = blind(msg, &mut rng).expect("Failed to blind message");
+
+        let shares: BTreeMap<_, _> = [1, 4, 5]
+            .iter()
+            .map(|&i| {
+                let sk_share = sk_set
+                    .secret_key_share(i)
+                    .unwrap_or_else(|_| panic!("Failed to create `SecretKeyShare` #{}", i));
+                let blind_share = sk_share.sign_g2(blinded);
+                assert!(pk_set.public_key_share(i).verify_g2(&blind_share, blinded));
+                let share = factor.unblind_share(&blind_share);
+                assert!(pk_set.public_key_share(i).verify(&share, msg));
+                (i, share)
+            })
+            .collect();
+
+        let sig = pk_set.combine_signatures(&shares).expect("shares match");
+        assert!(pk_set.public_key().verify(&sig, msg));
+    }
+}
@@ -0,0 +1,207 @@
+//! A common coin: unbiased pseudorandom values derived from a threshold signature.
+//!
+//! Since any `threshold + 1` signature shares combine into the same signature, and nobody can
+//! compute it with fewer shares, the signature of e.g. an epoch number can serve as a source of
+//! randomness that all participants agree on, but no coalition of up to `threshold` participants
+//! can predict or bias. A `Coin` extracts an arbitrary number of uniformly distributed bits from
+//! such a signature, by hashing it in counter mode.
+//!
+//! Each coin is bound to a _domain_ string. Coins with different domains are independent, even if
+//! they are derived from the same signature, so a single signature can be used for several
+//! purposes.
+
+use std::fmt;
+
+use byteorder::{BigEndian, ByteOrder};
+use tiny_keccak::sha3_256;
+
+use super::Signature;
+
+/// The domain separation tag for deriving a coin's seed from a signature.
+const COIN_DST: &[u8] = b"threshold_crypto common coin";
+
+/// The number of bytes produced by one invocation of the hash function.
+const BLOCK_SIZE: usize = 32;
+
+/// A stream of pseudorandom values, derived from a signature.
+///
+/// All participants who use the same signature and domain obtain the same stream of values.
+#[derive(Clone)]
+pub struct Coin {
+    /// The hash of the signature and domain.
+    seed: [u8; BLOCK_SIZE],
+    /// The index of the next block to be generated.
+    counter: u64,
+    /// The current block.
+    block: [u8; BLOCK_SIZE],
+    /// The number of bits in `block` that have already been used.
+    used_bits: usize,
+}
+
+impl fmt::Debug for Coin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Coin {{ counter: {}, .. }}", self.counter)
+    }
+}
+
+impl Signature {
+    /// Returns a coin derived from this signature and the given domain.
+    pub fn coin<D: AsRef<[u8]>>(&self, domain: D) -> Coin {
+        Coin::new(self, domain.as_ref())
+    }
+}
+
+impl Coin {
+    /// Creates a new coin from the signature and domain.
+    fn new(sig: &Signature, domain: &[u8]) -> Self {
+        let mut len = [0; 8];
+        BigEndian::write_u64(&mut len, domain.len() as u64);
+        let mut msg = COIN_DST.to_vec();
+        msg.extend(&len);
+        msg.extend(domain);
+        msg.extend(sig.to_bytes());
+        Coin {
+            seed: sha3_256(&msg),
+            counter: 0,
+            block: [0; BLOCK_SIZE],
+            used_bits: 8 * BLOCK_SIZE,
+        }
+    }
+
+    /// Returns the next pseudorandom bit.
+    pub fn bit(&mut self) -> bool {
+        if self.used_bits == 8 * BLOCK_SIZE {
+            self.next_block();
+        }
+        let byte = self.block[self.used_bits / 8];
+        let bit = byte & (0x80 >> (self.used_bits % 8)) != 0;
+        self.used_bits += 1;
+        bit
+    }
+
+    /// Returns the next `n` pseudorandom bits.
+    pub fn bits(&mut self, n: usize) -> Vec<bool> {
+        (0..n).map(|_| self.bit()).collect()
+    }
+
+    /// Returns the next pseudorandom `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        (0..64).fold(0, |acc, _| (acc << 1) | u64::from(self.bit()))
+    }
+
+    /// Returns a uniformly distributed integer in the range `0..n`.
+    ///
+    /// This uses rejection sampling, so there is no modulo bias.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    pub fn gen_range(&mut self, n: u64) -> u64 {
+        assert_ne!(0, n, "the range must not be empty");
+        // Only use the lowest `bits` bits, so that less than half of the samples are rejected.
+        let bits = 64 - (n - 1).leading_zeros() as usize;
+        loop {
+            let x = (0..bits).fold(0, |acc, _| (acc << 1) | u64::from(self.bit()));
+            if x < n {
+                return x;
+            }
+        }
+    }
+
+    /// Shuffles the slice: Every permutation is equally likely.
+    ///
+    /// E.g. to agree on a random order of node IDs, all nodes can sort the IDs first and then
+    /// shuffle them using the same coin.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        // Fisher-Yates: Swap each item with a random one among itself and those before it.
+        for i in (1..items.len()).rev() {
+            let j = self.gen_range(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+
+    /// Computes the next block of pseudorandom bytes.
+    fn next_block(&mut self) {
+        let mut msg = self.seed.to_vec();
+        let mut counter = [0; 8];
+        BigEndian::write_u64(&mut counter, self.counter);
+        msg.extend(&counter);
+        self.block = sha3_256(&msg);
+        self.counter += 1;
+        self.used_bits = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rand::{self, random};
+
+    use super::super::{SecretKey, SecretKeySet};
+
+    #[test]
+    fn test_threshold_coin() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng).expect("Failed to create `SecretKeySet`");
+        let pk_set = sk_set.public_keys();
+        let sig_for = |indices: &[usize]| {
+            let shares: BTreeMap<_, _> = indices
+                .iter()
+                .map(|&i| {
+                    let sk_share = sk_set
+                        .secret_key_share(i)
+                        .unwrap_or_else(|_| panic!("Failed to create `SecretKeyShare` #{}", i));
+                    (i, sk_share.sign("epoch 3"))
+                })
+                .collect();
+            pk_set.combine_signatures(&shares).expect("shares match")
+        };
+
+        // Different sets of signers obtain the same values.
+        let sig0 = sig_for(&[0, 1, 2]);
+        let sig1 = sig_for(&[3, 5, 7]);
+        assert_eq!(sig0.coin("bits").bits(300), sig1.coin("bits").bits(300));
+        assert_eq!(
+            sig0.coin("range").gen_range(1000),
+            sig1.coin("range").gen_range(1000)
+        );
+
+        // Different domains yield independent coins.
+        assert_ne!(sig0.coin("a").bits(64), sig0.coin("b").bits(64));
+    }
+
+    #[test]
+    fn test_gen_range() {
+        let sk: SecretKey = random();
+        let mut coin = sk.sign("test").coin("gen_range");
+        let mut counts = [0; 5];
+        for _ in 0..1000 {
+            counts[coin.gen_range(5) as usize] += 1;
+        }
+        // Each value has probability 1/5; it is practically impossible to be off by that much.
+        for &count in &counts {
+            assert!(
+                count > 100 && count < 300,
+                "unexpected counts: {:?}",
+                counts
+            );
+        }
+        assert_eq!(0, coin.gen_range(1));
+    }
+
+    #[test]
+    fn test_shuffle() {
+        let sk: SecretKey = random();
+        let sig = sk.sign("test");
+        let node_ids: Vec<u32> = (0..20).collect();
+        let mut perm0 = node_ids.clone();
+        sig.coin("shuffle").shuffle(&mut perm0);
+        let mut perm1 = node_ids.clone();
+        sig.coin("shuffle").shuffle(&mut perm1);
+        assert_eq!(perm0, perm1);
+        assert_ne!(node_ids, perm0); // Fails with probability 1 / 20!.
+        perm0.sort();
+        assert_eq!(node_ids, perm0);
+    }
+}
@@ -0,0 +1,856 @@
+//! Threshold-issued anonymous credentials, following Coconut.
+//!
+//! A credential is a Pointcheval–Sanders signature on a list of attributes `m_1, ..., m_q`: The
+//! issuer's secret key consists of field elements `x, y_1, ..., y_q`, and a credential is a pair
+//! `(h, s)` of elements of `G2`, with `s = (x + y_1 m_1 + ... + y_q m_q) * h`. Like the master
+//! key of a `SecretKeySet`, each of the issuer's secret elements is shared among the authorities
+//! using a polynomial, so any `threshold + 1` authorities can issue a credential, but no coalition
+//! of up to `threshold` authorities can forge one.
+//!
+//! Issuance is blind with respect to the private attributes:
+//!
+//! * The user calls `request` with their attributes, and sends the `CredentialRequest` to the
+//!   authorities. It contains a commitment to the private attributes, their ElGamal encryptions
+//!   under a fresh key, and a proof that both are consistent.
+//! * Each authority verifies the request and returns a `BlindCredentialShare`.
+//! * The user decrypts each of them with the `RequestSecret` into a `CredentialShare`, verifies it
+//!   against the authority's public key share, and combines `threshold + 1` of them into a
+//!   `Credential`.
+//!
+//! To use the credential, the user creates a `CredentialProof` that discloses a subset of the
+//! attributes. The proof is re-randomized, so that several proofs from the same credential can't
+//! be linked to each other or to the issuance, and it is bound to a context, e.g. a verifier's
+//! challenge, so that it can't be replayed elsewhere.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use byteorder::{BigEndian, ByteOrder};
+use pairing::bls12_381::{Bls12, Fr, G1Affine, G1, G2};
+use pairing::{CurveAffine, CurveProjective, Engine, Field, PrimeField, PrimeFieldRepr};
+use rand::Rng;
+
+use super::error::{Error, Result};
+use super::{
+    hash_fr, hash_g2, interpolate, serde_impl, IntoFr, PublicKey, PublicKeySet, PublicKeyShare,
+    SecretKey, SecretKeySet, SecretKeyShare,
+};
+
+/// The domain separation tag for the attribute commitment generators.
+const GENERATOR_DST: &[u8] = b"threshold_crypto credential generator";
+
+/// The domain separation tag for hashing a request to the credential's base point `h`.
+const BASE_DST: &[u8] = b"threshold_crypto credential base";
+
+/// The domain separation tag for the challenge of the issuance proof.
+const REQUEST_DST: &[u8] = b"threshold_crypto credential request";
+
+/// The domain separation tag for the challenge of the show proof.
+const SHOW_DST: &[u8] = b"threshold_crypto credential show";
+
+/// The authorities' secret keys: one set of shares for `x`, and one for each attribute's `y_j`.
+pub struct IssuerKeySet {
+    x: SecretKeySet,
+    y: Vec<SecretKeySet>,
+}
+
+impl fmt::Debug for IssuerKeySet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "IssuerKeySet {{ attributes: {}, .. }}", self.y.len())
+    }
+}
+
+impl IssuerKeySet {
+    /// Creates a random key for credentials with the given number of attributes, shared so that
+    /// any `threshold + 1` authorities can issue credentials.
+    pub fn random<R: Rng>(attributes: usize, threshold: usize, rng: &mut R) -> Result<Self> {
+        let x = SecretKeySet::random(threshold, rng)?;
+        let y = (0..attributes)
+            .map(|_| SecretKeySet::random(threshold, rng))
+            .collect::<Result<_>>()?;
+        Ok(IssuerKeySet { x, y })
+    }
+
+    /// Returns the threshold `t`: any set of `t + 1` credential shares can be combined into a
+    /// credential.
+    pub fn threshold(&self) -> usize {
+        self.x.threshold()
+    }
+
+    /// Returns the `i`-th authority's key share.
+    pub fn secret_key_share<T: IntoFr>(&self, i: T) -> Result<IssuerKeyShare> {
+        Ok(IssuerKeyShare {
+            x: self.x.secret_key_share(i)?,
+            y: self
+                .y
+                .iter()
+                .map(|y| y.secret_key_share(i))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// Returns the corresponding public keys.
+    pub fn public_keys(&self) -> IssuerPublicKeySet {
+        IssuerPublicKeySet {
+            alpha: self.x.public_keys(),
+            beta: self.y.iter().map(SecretKeySet::public_keys).collect(),
+        }
+    }
+}
+
+/// An authority's share of the issuer's secret key.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct IssuerKeyShare {
+    x: SecretKeyShare,
+    y: Vec<SecretKeyShare>,
+}
+
+impl IssuerKeyShare {
+    /// Verifies the request and returns this authority's share of the credential, encrypted
+    /// to the user.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidProof` if the request is for the wrong number of attributes, or
+    /// if its proof is invalid.
+    pub fn issue(&self, req: &CredentialRequest) -> Result<BlindCredentialShare> {
+        if req.a.len() + req.public.len() != self.y.len() || !req.verify() {
+            return Err(Error::InvalidProof);
+        }
+        let h = req.base();
+        let (y_private, y_public) = self.y.split_at(req.a.len());
+        // The public part of the exponent, `x + y_j m_j` for the public attributes.
+        let mut exp = *(self.x.0).0;
+        for (y, m) in y_public.iter().zip(&req.public) {
+            let mut ym = *(y.0).0;
+            ym.mul_assign(m);
+            exp.add_assign(&ym);
+        }
+        let mut a = G2::zero();
+        let mut b = mul(h, &exp);
+        for ((y, a_j), b_j) in y_private.iter().zip(&req.a).zip(&req.b) {
+            a.add_assign(&mul(*a_j, &(y.0).0));
+            b.add_assign(&mul(*b_j, &(y.0).0));
+        }
+        Ok(BlindCredentialShare { h, a, b })
+    }
+}
+
+/// The public keys of the authorities.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct IssuerPublicKeySet {
+    alpha: PublicKeySet,
+    beta: Vec<PublicKeySet>,
+}
+
+impl IssuerPublicKeySet {
+    /// Returns the threshold `t`: any set of `t + 1` credential shares can be combined into a
+    /// credential.
+    pub fn threshold(&self) -> usize {
+        self.alpha.threshold()
+    }
+
+    /// Returns the number of attributes.
+    pub fn attributes(&self) -> usize {
+        self.beta.len()
+    }
+
+    /// Returns the issuer's public key, which verifies credentials.
+    pub fn public_key(&self) -> IssuerPublicKey {
+        IssuerPublicKey {
+            alpha: self.alpha.public_key(),
+            beta: self.beta.iter().map(PublicKeySet::public_key).collect(),
+        }
+    }
+
+    /// Returns the `i`-th authority's public key share, which verifies its credential shares.
+    pub fn public_key_share<T: IntoFr>(&self, i: T) -> IssuerPublicKeyShare {
+        IssuerPublicKeyShare {
+            alpha: self.alpha.public_key_share(i),
+            beta: self.beta.iter().map(|b| b.public_key_share(i)).collect(),
+        }
+    }
+
+    /// Combines the shares into a credential that can be verified with the issuer's public key.
+    ///
+    /// The shares are not verified: Use `IssuerPublicKeyShare::verify` to check them first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidShare` if the shares don't belong to the same request.
+    pub fn combine_credentials<'a, T, I>(&self, shares: I) -> Result<Credential>
+    where
+        I: IntoIterator<Item = (T, &'a CredentialShare)>,
+        T: IntoFr,
+    {
+        let shares: Vec<_> = shares.into_iter().collect();
+        let h = match shares.first() {
+            Some((_, share)) => (share.0).h,
+            None => return Err(Error::NotEnoughShares),
+        };
+        if shares.iter().any(|(_, share)| (share.0).h != h) {
+            return Err(Error::InvalidShare);
+        }
+        let samples = shares.into_iter().map(|(i, share)| (i, &(share.0).s));
+        let s = interpolate(self.threshold() + 1, samples)?;
+        Ok(Credential { h, s })
+    }
+}
+
+/// The issuer's public key: `x * g1` and `y_j * g1` for each attribute.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct IssuerPublicKey {
+    alpha: PublicKey,
+    beta: Vec<PublicKey>,
+}
+
+impl IssuerPublicKey {
+    /// Returns `true` if the credential is valid for the given attributes.
+    pub fn verify(&self, cred: &Credential, attributes: &[Fr]) -> bool {
+        verify_credential(&self.alpha, &self.beta, cred, attributes)
+    }
+
+    /// Returns `true` if the proof shows a valid credential with the given disclosed attributes,
+    /// and was created for the given context.
+    pub fn verify_proof<C: AsRef<[u8]>>(
+        &self,
+        proof: &CredentialProof,
+        disclosed: &BTreeMap<usize, Fr>,
+        context: C,
+    ) -> bool {
+        let hidden: Vec<_> = (0..self.beta.len())
+            .filter(|j| !disclosed.contains_key(j))
+            .collect();
+        if disclosed.keys().any(|&j| j >= self.beta.len())
+            || hidden.len() != proof.attribute_responses.len()
+            || proof.h.is_zero()
+        {
+            return false;
+        }
+        // Recompute the prover's commitments from the responses.
+        let mut kappa_alpha = proof.kappa;
+        kappa_alpha.sub_assign(&(self.alpha.0));
+        let mut com_kappa = mul(kappa_alpha, &proof.challenge);
+        com_kappa.add_assign(&mul(G1::one(), &proof.t_response));
+        for (&j, r) in hidden.iter().zip(&proof.attribute_responses) {
+            com_kappa.add_assign(&mul((self.beta[j]).0, r));
+        }
+        let mut com_nu = mul(proof.nu, &proof.challenge);
+        com_nu.add_assign(&mul(proof.h, &proof.t_response));
+        let c = show_challenge(context.as_ref(), proof, disclosed, &com_kappa, &com_nu);
+        if c != proof.challenge {
+            return false;
+        }
+        let mut lhs = proof.kappa;
+        for (&j, m) in disclosed {
+            lhs.add_assign(&mul((self.beta[j]).0, m));
+        }
+        Bls12::pairing(lhs, proof.h) == Bls12::pairing(G1Affine::one(), proof.s)
+    }
+}
+
+/// An authority's public key share, which verifies its credential shares.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct IssuerPublicKeyShare {
+    alpha: PublicKeyShare,
+    beta: Vec<PublicKeyShare>,
+}
+
+impl IssuerPublicKeyShare {
+    /// Returns `true` if the credential share is valid for the given attributes.
+    pub fn verify(&self, share: &CredentialShare, attributes: &[Fr]) -> bool {
+        let beta: Vec<_> = self.beta.iter().map(|b| b.0).collect();
+        verify_credential(&self.alpha.0, &beta, &share.0, attributes)
+    }
+}
+
+/// A credential: a Pointcheval–Sanders signature `(h, s)` on a list of attributes.
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Credential {
+    #[serde(with = "serde_impl::projective")]
+    h: G2,
+    #[serde(with = "serde_impl::projective")]
+    s: G2,
+}
+
+impl Credential {
+    /// Returns an equivalent credential `(r * h, r * s)`, for a random non-zero `r`. It is valid
+    /// for the same attributes, but can't be linked to the original one.
+    pub fn randomize<R: Rng>(&self, rng: &mut R) -> Credential {
+        let r = random_nonzero(rng);
+        Credential {
+            h: mul(self.h, &r),
+            s: mul(self.s, &r),
+        }
+    }
+
+    /// Returns a proof that the user holds a credential for the given attributes, which discloses
+    /// only the attributes with the given indices.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of attributes doesn't match the public key.
+    pub fn prove<C, R>(
+        &self,
+        pk: &IssuerPublicKey,
+        attributes: &[Fr],
+        disclosed: &[usize],
+        context: C,
+        rng: &mut R,
+    ) -> CredentialProof
+    where
+        C: AsRef<[u8]>,
+        R: Rng,
+    {
+        assert_eq!(
+            pk.beta.len(),
+            attributes.len(),
+            "wrong number of attributes"
+        );
+        let Credential { h, s } = self.randomize(rng);
+        let hidden: Vec<_> = (0..attributes.len())
+            .filter(|j| !disclosed.contains(j))
+            .collect();
+        // `kappa = alpha + t * g1 + sum(m_j * beta_j)` over the hidden attributes, `nu = t * h`.
+        let t: Fr = rng.gen();
+        let mut kappa = pk.alpha.0;
+        kappa.add_assign(&mul(G1::one(), &t));
+        for &j in &hidden {
+            kappa.add_assign(&mul(pk.beta[j].0, &attributes[j]));
+        }
+        let nu = mul(h, &t);
+        let mut s_nu = s;
+        s_nu.add_assign(&nu);
+
+        // Prove knowledge of `t` and the hidden attributes.
+        let w_t: Fr = rng.gen();
+        let w_m: Vec<Fr> = hidden.iter().map(|_| rng.gen()).collect();
+        let mut com_kappa = mul(G1::one(), &w_t);
+        for (&j, w) in hidden.iter().zip(&w_m) {
+            com_kappa.add_assign(&mul(pk.beta[j].0, w));
+        }
+        let com_nu = mul(h, &w_t);
+        let disclosed_attrs: BTreeMap<_, _> =
+            disclosed.iter().map(|&j| (j, attributes[j])).collect();
+        let mut proof = CredentialProof {
+            h,
+            s: s_nu,
+            kappa,
+            nu,
+            challenge: Fr::zero(),
+            t_response: Fr::zero(),
+            attribute_responses: Vec::new(),
+        };
+        let c = show_challenge(
+            context.as_ref(),
+            &proof,
+            &disclosed_attrs,
+            &com_kappa,
+            &com_nu,
+        );
+        proof.challenge = c;
+        proof.t_response = response(w_t, &c, &t);
+        proof.attribute_responses = hidden
+            .iter()
+            .zip(w_m)
+            .map(|(&j, w)| response(w, &c, &attributes[j]))
+            .collect();
+        proof
+    }
+}
+
+/// An authority's share of a credential, decrypted by the user.
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct CredentialShare(pub Credential);
+
+/// An authority's share of a credential, with the private attributes' part still encrypted to
+/// the user.
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct BlindCredentialShare {
+    #[serde(with = "serde_impl::projective")]
+    h: G2,
+    #[serde(with = "serde_impl::projective")]
+    a: G2,
+    #[serde(with = "serde_impl::projective")]
+    b: G2,
+}
+
+/// A request for a credential, which reveals only the public attributes.
+///
+/// The first attributes are private: They are committed to in `commitment`, and encrypted as
+/// `(a_j, b_j) = (k_j * g2, k_j * gamma + m_j * h)` under the user's ephemeral ElGamal key
+/// `gamma`. The proof shows that the encryptions match the commitment.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct CredentialRequest {
+    #[serde(with = "serde_impl::projective")]
+    gamma: G2,
+    #[serde(with = "serde_impl::projective")]
+    commitment: G2,
+    #[serde(with = "serde_impl::projective_vec")]
+    a: Vec<G2>,
+    #[serde(with = "serde_impl::projective_vec")]
+    b: Vec<G2>,
+    #[serde(with = "serde_impl::field_vec")]
+    public: Vec<Fr>,
+    proof: RequestProof,
+}
+
+/// A proof of knowledge of the private attributes and the randomness in a `CredentialRequest`.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+struct RequestProof {
+    #[serde(with = "serde_impl::field")]
+    challenge: Fr,
+    #[serde(with = "serde_impl::field")]
+    o_response: Fr,
+    #[serde(with = "serde_impl::field_vec")]
+    m_responses: Vec<Fr>,
+    #[serde(with = "serde_impl::field_vec")]
+    k_responses: Vec<Fr>,
+}
+
+impl CredentialRequest {
+    /// Returns the public attributes.
+    pub fn public_attributes(&self) -> &[Fr] {
+        &self.public
+    }
+
+    /// Returns the credential's base point `h`, which is derived from the commitment and the
+    /// public attributes.
+    fn base(&self) -> G2 {
+        let mut msg = BASE_DST.to_vec();
+        append_point(&mut msg, &self.commitment);
+        for m in &self.public {
+            append_fr(&mut msg, m);
+        }
+        hash_g2(&msg)
+    }
+
+    /// Returns `true` if the proof is valid.
+    fn verify(&self) -> bool {
+        let proof = &self.proof;
+        let n = self.a.len();
+        if self.b.len() != n || proof.m_responses.len() != n || proof.k_responses.len() != n {
+            return false;
+        }
+        let h = self.base();
+        let c = &proof.challenge;
+        let mut com_cm = mul(self.commitment, c);
+        com_cm.add_assign(&mul(G2::one(), &proof.o_response));
+        for (j, r_m) in proof.m_responses.iter().enumerate() {
+            com_cm.add_assign(&mul(generator(j), r_m));
+        }
+        let com_a: Vec<_> = self
+            .a
+            .iter()
+            .zip(&proof.k_responses)
+            .map(|(a, r_k)| {
+                let mut com = mul(*a, c);
+                com.add_assign(&mul(G2::one(), r_k));
+                com
+            })
+            .collect();
+        let com_b: Vec<_> = self
+            .b
+            .iter()
+            .zip(&proof.k_responses)
+            .zip(&proof.m_responses)
+            .map(|((b, r_k), r_m)| {
+                let mut com = mul(*b, c);
+                com.add_assign(&mul(self.gamma, r_k));
+                com.add_assign(&mul(h, r_m));
+                com
+            })
+            .collect();
+        *c == self.challenge(&h, &com_cm, &com_a, &com_b)
+    }
+
+    /// Returns the proof's challenge for the given prover's commitments.
+    fn challenge(&self, h: &G2, com_cm: &G2, com_a: &[G2], com_b: &[G2]) -> Fr {
+        let mut msg = REQUEST_DST.to_vec();
+        for point in [&self.gamma, &self.commitment, h, com_cm].iter() {
+            append_point(&mut msg, *point);
+        }
+        for point in self.a.iter().chain(&self.b).chain(com_a).chain(com_b) {
+            append_point(&mut msg, point);
+        }
+        hash_fr(&msg)
+    }
+}
+
+/// The user's secret state for a `CredentialRequest`: the attributes and the ElGamal key.
+pub struct RequestSecret {
+    attributes: Vec<Fr>,
+    h: G2,
+    key: SecretKey,
+}
+
+impl fmt::Debug for RequestSecret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RequestSecret(...)")
+    }
+}
+
+impl RequestSecret {
+    /// Returns all attributes, private and public.
+    pub fn attributes(&self) -> &[Fr] {
+        &self.attributes
+    }
+
+    /// Decrypts an authority's credential share.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidShare` if the share was issued for a different request.
+    pub fn unblind_share(&self, share: &BlindCredentialShare) -> Result<CredentialShare> {
+        if share.h != self.h {
+            return Err(Error::InvalidShare);
+        }
+        let mut s = share.b;
+        s.sub_assign(&mul(share.a, &self.key.0));
+        Ok(CredentialShare(Credential { h: share.h, s }))
+    }
+}
+
+/// Creates a request for a credential on the given attributes, of which the first `private` ones
+/// are hidden from the authorities. Returns the request together with the secret needed to
+/// decrypt the authorities' responses.
+///
+/// # Panics
+///
+/// Panics if `private` is greater than the number of attributes.
+pub fn request<R: Rng>(
+    attributes: &[Fr],
+    private: usize,
+    rng: &mut R,
+) -> Result<(CredentialRequest, RequestSecret)> {
+    let (private_attrs, public_attrs) = attributes.split_at(private);
+    let mut fr: Fr = rng.gen();
+    let key = SecretKey::from_mut_ptr(&mut fr as *mut Fr)?;
+    let gamma = mul(G2::one(), &key.0);
+    let o: Fr = rng.gen();
+    let mut commitment = mul(G2::one(), &o);
+    for (j, m) in private_attrs.iter().enumerate() {
+        commitment.add_assign(&mul(generator(j), m));
+    }
+    let mut req = CredentialRequest {
+        gamma,
+        commitment,
+        a: Vec::with_capacity(private),
+        b: Vec::with_capacity(private),
+        public: public_attrs.to_vec(),
+        proof: RequestProof {
+            challenge: Fr::zero(),
+            o_response: Fr::zero(),
+            m_responses: Vec::new(),
+            k_responses: Vec::new(),
+        },
+    };
+    let h = req.base();
+    let k: Vec<Fr> = private_attrs.iter().map(|_| rng.gen()).collect();
+    for (k_j, m) in k.iter().zip(private_attrs) {
+        req.a.push(mul(G2::one(), k_j));
+        let mut b = mul(gamma, k_j);
+        b.add_assign(&mul(h, m));
+        req.b.push(b);
+    }
+
+    // Prove knowledge of `o`, the private attributes and the `k_j`.
+    let w_o: Fr = rng.gen();
+    let w_m: Vec<Fr> = private_attrs.iter().map(|_| rng.gen()).collect();
+    let w_k: Vec<Fr> = private_attrs.iter().map(|_| rng.gen()).collect();
+    let mut com_cm = mul(G2::one(), &w_o);
+    for (j, w) in w_m.iter().enumerate() {
+        com_cm.add_assign(&mul(generator(j), w));
+    }
+    let com_a: Vec<_> = w_k.iter().map(|w| mul(G2::one(), w)).collect();
+    let com_b: Vec<_> = w_k
+        .iter()
+        .zip(&w_m)
+        .map(|(w_k, w_m)| {
+            let mut com = mul(gamma, w_k);
+            com.add_assign(&mul(h, w_m));
+            com
+        })
+        .collect();
+    let c = req.challenge(&h, &com_cm, &com_a, &com_b);
+    req.proof = RequestProof {
+        challenge: c,
+        o_response: response(w_o, &c, &o),
+        m_responses: w_m
+            .into_iter()
+            .zip(private_attrs)
+            .map(|(w, m)| response(w, &c, m))
+            .collect(),
+        k_responses: w_k
+            .into_iter()
+            .zip(&k)
+            .map(|(w, k_j)| response(w, &c, k_j))
+            .collect(),
+    };
+    let secret = RequestSecret {
+        attributes: attributes.to_vec(),
+        h,
+        key,
+    };
+    Ok((req, secret))
+}
+
+/// A zero-knowledge proof of a credential, disclosing a subset of its attributes.
+///
+/// It contains a randomized credential `(h, s + nu)`, `kappa = alpha + t * g1 + sum(m_j * beta_j)`
+/// over the hidden attributes, and `nu = t * h`, together with a proof of knowledge of `t` and the
+/// hidden attributes.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct CredentialProof {
+    #[serde(with = "serde_impl::projective")]
+    h: G2,
+    #[serde(with = "serde_impl::projective")]
+    s: G2,
+    #[serde(with = "serde_impl::projective")]
+    kappa: G1,
+    #[serde(with = "serde_impl::projective")]
+    nu: G2,
+    #[serde(with = "serde_impl::field")]
+    challenge: Fr,
+    #[serde(with = "serde_impl::field")]
+    t_response: Fr,
+    #[serde(with = "serde_impl::field_vec")]
+    attribute_responses: Vec<Fr>,
+}
+
+/// Returns the challenge for a `CredentialProof`.
+fn show_challenge(
+    context: &[u8],
+    proof: &CredentialProof,
+    disclosed: &BTreeMap<usize, Fr>,
+    com_kappa: &G1,
+    com_nu: &G2,
+) -> Fr {
+    let mut msg = SHOW_DST.to_vec();
+    let mut len = [0; 8];
+    BigEndian::write_u64(&mut len, context.len() as u64);
+    msg.extend(&len);
+    msg.extend(context);
+    for (&j, m) in disclosed {
+        BigEndian::write_u64(&mut len, j as u64);
+        msg.extend(&len);
+        append_fr(&mut msg, m);
+    }
+    for point in [&proof.h, &proof.s, &proof.nu, com_nu].iter() {
+        append_point(&mut msg, *point);
+    }
+    append_point(&mut msg, &proof.kappa);
+    append_point(&mut msg, com_kappa);
+    hash_fr(&msg)
+}
+
+/// Returns `true` if `(h, s)` is a valid credential for the key `(alpha, beta)`.
+fn verify_credential(
+    alpha: &PublicKey,
+    beta: &[PublicKey],
+    cred: &Credential,
+    attributes: &[Fr],
+) -> bool {
+    if beta.len() != attributes.len() || cred.h.is_zero() {
+        return false;
+    }
+    let mut key = alpha.0;
+    for (b, m) in beta.iter().zip(attributes) {
+        key.add_assign(&mul(b.0, m));
+    }
+    Bls12::pairing(key, cred.h) == Bls12::pairing(G1Affine::one(), cred.s)
+}
+
+/// Returns the generator in `G2` for the `j`-th attribute in a commitment.
+fn generator(j: usize) -> G2 {
+    let mut msg = GENERATOR_DST.to_vec();
+    let mut index = [0; 8];
+    BigEndian::write_u64(&mut index, j as u64);
+    msg.extend(&index);
+    hash_g2(&msg)
+}
+
+/// Returns the product of the group element and the scalar.
+fn mul<C: CurveProjective<Scalar = Fr>>(mut point: C, scalar: &Fr) -> C {
+    point.mul_assign(*scalar);
+    point
+}
+
+/// Returns the Schnorr response `w - c * x`.
+fn response(mut w: Fr, c: &Fr, x: &Fr) -> Fr {
+    let mut cx = *c;
+    cx.mul_assign(x);
+    w.sub_assign(&cx);
+    w
+}
+
+/// Returns a random non-zero field element.
+fn random_nonzero<R: Rng>(rng: &mut R) -> Fr {
+    loop {
+        let r: Fr = rng.gen();
+        if !r.is_zero() {
+            return r;
+        }
+    }
+}
+
+/// Appends the compressed representation of the group element.
+fn append_point<C: CurveProjective>(msg: &mut Vec<u8>, point: &C) {
+    msg.extend(point.into_affine().into_compressed().as_ref());
+}
+
+/// Appends the big-endian representation of the field element.
+fn append_fr(msg: &mut Vec<u8>, fr: &Fr) {
+    fr.into_repr()
+        .write_be(msg)
+        .expect("writing to a vector never fails");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rand;
+
+    use super::*;
+
+    /// Issues a credential for the given attributes, using the authorities with the given
+    /// indices.
+    fn issue(
+        key_set: &IssuerKeySet,
+        attributes: &[Fr],
+        private: usize,
+        indices: &[usize],
+    ) -> Credential {
+        let mut rng = rand::thread_rng();
+        let pk_set = key_set.public_keys();
+        let (req, secret) = request(attributes, private, &mut rng).expect("request");
+        let shares: BTreeMap<_, _> = indices
+            .iter()
+            .map(|&i| {
+                let key_share = key_set.secret_key_share(i).expect("key share");
+                let blind_share = key_share.issue(&req).expect("valid request");
+                let share = secret.unblind_share(&blind_share).expect("matching share");
+                assert!(pk_set.public_key_share(i).verify(&share, attributes));
+                assert!(!pk_set.public_key_share(i + 1).verify(&share, attributes));
+                (i, share)
+            })
+            .collect();
+        pk_set.combine_credentials(&shares).expect("shares match")
+    }
+
+    fn attributes() -> Vec<Fr> {
+        // A secret ID, a secret vote weight, a public district and a public expiry date.
+        vec![
+            hash_fr("user secret"),
+            42u64.into_fr(),
+            7u64.into_fr(),
+            20301231u64.into_fr(),
+        ]
+    }
+
+    #[test]
+    fn test_issue_credential() {
+        let mut rng = rand::thread_rng();
+        let key_set = IssuerKeySet::random(4, 2, &mut rng).expect("key set");
+        let pk = key_set.public_keys().public_key();
+        let attrs = attributes();
+
+        let cred = issue(&key_set, &attrs, 2, &[0, 3, 4]);
+        assert!(pk.verify(&cred, &attrs));
+        assert!(pk.verify(&cred.randomize(&mut rng), &attrs));
+        let mut wrong_attrs = attrs.clone();
+        wrong_attrs[1] = 43u64.into_fr();
+        assert!(!pk.verify(&cred, &wrong_attrs));
+
+        // Different authorities and fully public or private requests work, too.
+        assert!(pk.verify(&issue(&key_set, &attrs, 0, &[1, 2, 5]), &attrs));
+        assert!(pk.verify(&issue(&key_set, &attrs, 4, &[2, 5, 6]), &attrs));
+    }
+
+    #[test]
+    fn test_invalid_request() {
+        let mut rng = rand::thread_rng();
+        let key_set = IssuerKeySet::random(4, 1, &mut rng).expect("key set");
+        let key_share = key_set.secret_key_share(0).expect("key share");
+        let attrs = attributes();
+
+        // The user can't claim different public attributes for the same commitment.
+        let (mut req, _) = request(&attrs, 2, &mut rng).expect("request");
+        req.public[0] = 8u64.into_fr();
+        assert_eq!(Err(Error::InvalidProof), key_share.issue(&req));
+
+        // Or change an encrypted attribute.
+        let (mut req, _) = request(&attrs, 2, &mut rng).expect("request");
+        req.b[1].add_assign(&G2::one());
+        assert_eq!(Err(Error::InvalidProof), key_share.issue(&req));
+
+        // Or request the wrong number of attributes.
+        let (req, _) = request(&attrs[..3], 2, &mut rng).expect("request");
+        assert_eq!(Err(Error::InvalidProof), key_share.issue(&req));
+
+        // Shares from different requests can't be unblinded or combined.
+        let (req0, secret0) = request(&attrs, 2, &mut rng).expect("request");
+        let (req1, secret1) = request(&attrs, 2, &mut rng).expect("request");
+        let blind_share1 = key_share.issue(&req1).expect("valid request");
+        assert_eq!(
+            Err(Error::InvalidShare),
+            secret0.unblind_share(&blind_share1)
+        );
+        let share0 = secret0
+            .unblind_share(&key_share.issue(&req0).expect("valid request"))
+            .expect("matching share");
+        let share1 = secret1
+            .unblind_share(&blind_share1)
+            .expect("matching share");
+        let pk_set = key_set.public_keys();
+        assert_eq!(
+            Err(Error::InvalidShare),
+            pk_set.combine_credentials(vec![(0, &share0), (1, &share1)])
+        );
+    }
+
+    #[test]
+    fn test_show_credential() {
+        let mut rng = rand::thread_rng();
+        let key_set = IssuerKeySet::random(4, 2, &mut rng).expect("key set");
+        let pk = key_set.public_keys().public_key();
+        let attrs = attributes();
+        let cred = issue(&key_set, &attrs, 2, &[0, 1, 2]);
+
+        // Disclose the district and expiry date, but not the ID and weight.
+        let proof = cred.prove(&pk, &attrs, &[2, 3], "poll 5", &mut rng);
+        let disclosed: BTreeMap<_, _> = vec![(2, attrs[2]), (3, attrs[3])].into_iter().collect();
+        assert!(pk.verify_proof(&proof, &disclosed, "poll 5"));
+        assert!(!pk.verify_proof(&proof, &disclosed, "poll 6"));
+        let mut wrong: BTreeMap<_, _> = disclosed.clone();
+        wrong.insert(2, 8u64.into_fr());
+        assert!(!pk.verify_proof(&proof, &wrong, "poll 5"));
+        wrong.remove(&2);
+        assert!(!pk.verify_proof(&proof, &wrong, "poll 5"));
+
+        // Two proofs of the same credential are unlinkable.
+        let proof2 = cred.prove(&pk, &attrs, &[2, 3], "poll 5", &mut rng);
+        assert!(pk.verify_proof(&proof2, &disclosed, "poll 5"));
+        assert_ne!(proof.h, proof2.h);
+        assert_ne!(proof.kappa, proof2.kappa);
+
+        // Nothing or everything can be disclosed.
+        let proof = cred.prove(&pk, &attrs, &[], "", &mut rng);
+        assert!(pk.verify_proof(&proof, &BTreeMap::new(), ""));
+        let all: BTreeMap<_, _> = attrs.iter().cloned().enumerate().collect();
+        let proof = cred.prove(&pk, &attrs, &[0, 1, 2, 3], "", &mut rng);
+        assert!(pk.verify_proof(&proof, &all, ""));
+
+        // A credential for different attributes doesn't yield a valid proof.
+        let mut other_attrs = attrs.clone();
+        other_attrs[0] = hash_fr("other user");
+        let proof = cred.prove(&pk, &other_attrs, &[2, 3], "poll 5", &mut rng);
+        assert!(!pk.verify_proof(&proof, &disclosed, "poll 5"));
+
+        // Serialization roundtrip.
+        let ser = ::bincode::serialize(&proof).expect("serialize");
+        let de: CredentialProof = ::bincode::deserialize(&ser).expect("deserialize");
+        assert_eq!(proof, de);
+    }
+}
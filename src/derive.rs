@@ -0,0 +1,237 @@
+//! Hierarchical deterministic key derivation, following
+//! [EIP-2333](https://eips.ethereum.org/EIPS/eip-2333).
+//!
+//! A master key is derived from a seed, and every key has `2^32` children. To derive a child, the
+//! parent key is expanded into two Lamport secret keys, and the hash of the corresponding Lamport
+//! public key is used as the child's input keying material. Knowing a child key therefore reveals
+//! nothing about its parent or siblings.
+//!
+//! A path like `m/12381/3600/0/0` denotes the key obtained by deriving the children with indices
+//! `12381`, `3600`, `0` and `0`, in that order, starting from the master key `m`.
+
+use hkdf::{Hkdf, HkdfExtract};
+use memsec::memzero;
+use pairing::bls12_381::Fr;
+use pairing::Field;
+use sha2::{Digest, Sha256};
+
+use super::error::{Error, Result};
+use super::{IntoFr, SecretKey, SK_SIZE};
+
+/// The initial salt of `HKDF_mod_r`.
+const KEYGEN_SALT: &[u8] = b"BLS-SIG-KEYGEN-SALT-";
+/// The number of bytes of keying material that are reduced modulo `r`, to obtain a key.
+const KEYGEN_LEN: u16 = 48;
+/// The number of chunks in a Lamport secret key.
+const LAMPORT_CHUNKS: usize = 255;
+
+impl SecretKey {
+    /// Derives the master key from the seed, which must be at least 32 bytes long. This is the
+    /// same as `SecretKey::key_gen(seed, "")`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::SeedTooShort` if the seed has fewer than 32 bytes, or an
+    /// `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn derive_master<S: AsRef<[u8]>>(seed: S) -> Result<Self> {
+        SecretKey::key_gen(seed, b"")
+    }
+
+    /// Derives the child key with the given index.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn derive_child(&self, index: u32) -> Result<Self> {
+        let mut ikm = [0u8; SK_SIZE];
+        self.to_bytes(&mut ikm);
+        let mut lamport_pk = Sha256::new();
+        lamport_chunk_hashes(&ikm, index, &mut lamport_pk);
+        for byte in ikm.iter_mut() {
+            *byte = !*byte;
+        }
+        lamport_chunk_hashes(&ikm, index, &mut lamport_pk);
+        unsafe {
+            memzero(ikm.as_mut_ptr(), ikm.len());
+        }
+        let mut child_ikm = lamport_pk.finalize();
+        let result = hkdf_mod_r(&child_ikm, b"");
+        unsafe {
+            memzero(child_ikm.as_mut_ptr(), child_ikm.len());
+        }
+        result
+    }
+
+    /// Derives the key with the given path, e.g. `m/12381/3600/0/0`, from the seed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidDerivationPath` if the path is malformed, an
+    /// `Error::SeedTooShort` if the seed has fewer than 32 bytes, or an `Error::MlockFailed` if we
+    /// have reached the systems's locked memory limit.
+    pub fn derive_path<S: AsRef<[u8]>>(seed: S, path: &str) -> Result<Self> {
+        let indices = parse_path(path)?;
+        let mut sk = SecretKey::derive_master(seed)?;
+        for index in indices {
+            sk = sk.derive_child(index)?;
+        }
+        Ok(sk)
+    }
+}
+
+/// Returns the child indices in the given path.
+fn parse_path(path: &str) -> Result<Vec<u32>> {
+    let mut parts = path.split('/');
+    if parts.next().map(str::trim) != Some("m") {
+        return Err(Error::InvalidDerivationPath);
+    }
+    parts
+        .map(|part| {
+            let part = part.trim();
+            if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(Error::InvalidDerivationPath);
+            }
+            part.parse().map_err(|_| Error::InvalidDerivationPath)
+        })
+        .collect()
+}
+
+/// Feeds the hashes of the chunks of the Lamport secret key derived from `ikm` into the hasher.
+fn lamport_chunk_hashes(ikm: &[u8], index: u32, hasher: &mut Sha256) {
+    let salt = index.to_be_bytes();
+    let mut okm = vec![0u8; LAMPORT_CHUNKS * 32];
+    Hkdf::<Sha256>::new(Some(&salt), ikm)
+        .expand(b"", &mut okm)
+        .expect("the output length is at most 255 times the hash length");
+    for chunk in okm.chunks(32) {
+        hasher.update(Sha256::digest(chunk));
+    }
+    unsafe {
+        memzero(okm.as_mut_ptr(), okm.len());
+    }
+}
+
+/// Returns the key derived from the input keying material, using HKDF with SHA-256 and reducing
+/// the output modulo `r`. If the result is zero, the salt is hashed again and the process is
+/// repeated.
+pub(crate) fn hkdf_mod_r(ikm: &[u8], key_info: &[u8]) -> Result<SecretKey> {
+    let mut salt = Sha256::digest(KEYGEN_SALT);
+    loop {
+        let mut extract = HkdfExtract::<Sha256>::new(Some(&salt));
+        extract.input_ikm(ikm);
+        extract.input_ikm(&[0]);
+        let (_, hkdf) = extract.finalize();
+        let mut okm = [0u8; KEYGEN_LEN as usize];
+        hkdf.expand_multi_info(&[key_info, &KEYGEN_LEN.to_be_bytes()], &mut okm)
+            .expect("the output length is less than 255 times the hash length");
+        let mut fr = Fr::zero();
+        let base = 256u64.into_fr();
+        for byte in okm.iter() {
+            fr.mul_assign(&base);
+            fr.add_assign(&u64::from(*byte).into_fr());
+        }
+        unsafe {
+            memzero(okm.as_mut_ptr(), okm.len());
+        }
+        if !fr.is_zero() {
+            return SecretKey::from_mut_ptr(&mut fr as *mut Fr);
+        }
+        salt = Sha256::digest(&salt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex;
+
+    use super::*;
+
+    /// Returns the key with the given decimal representation.
+    fn sk_from_decimal(decimal: &str) -> SecretKey {
+        let mut fr = Fr::zero();
+        for digit in decimal.bytes() {
+            fr.mul_assign(&10u64.into_fr());
+            fr.add_assign(&u64::from(digit - b'0').into_fr());
+        }
+        SecretKey::from_mut_ptr(&mut fr as *mut Fr).expect("Failed to create key")
+    }
+
+    #[test]
+    fn test_eip2333_vectors() {
+        // The test cases from EIP-2333: seed, master key, child index and child key.
+        let vectors = [
+            (
+                "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+                "6083874454709270928345386274498605044986640685124978867557563392430687146096",
+                0,
+                "20397789859736650942317412262472558107875392172444076792671091975210932703118",
+            ),
+            (
+                "3141592653589793238462643383279502884197169399375105820974944592",
+                "29757020647961307431480504535336562678282505419141012933316116377660817309383",
+                3_141_592_653,
+                "25457201688850691947727629385191704516744796114925897962676248250929345014287",
+            ),
+            (
+                "0099FF991111002299DD7744EE3355BBDD8844115566CC55663355668888CC00",
+                "27580842291869792442942448775674722299803720648445448686099262467207037398656",
+                4_294_967_295,
+                "29358610794459428860402234341874281240803786294062035874021252734817515685787",
+            ),
+            (
+                "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3",
+                "19022158461524446591288038168518313374041767046816487870552872741050760015818",
+                42,
+                "31372231650479070279774297061823572166496564838472787488249775572789064611981",
+            ),
+        ];
+        for (seed, master, index, child) in &vectors {
+            let seed = hex::decode(seed).expect("valid hex");
+            let master_sk = SecretKey::derive_master(&seed).expect("Failed to derive master key");
+            assert_eq!(sk_from_decimal(master), master_sk);
+            let child_sk = master_sk
+                .derive_child(*index)
+                .expect("Failed to derive child");
+            assert_eq!(sk_from_decimal(child), child_sk);
+            let path = format!("m/{}", index);
+            assert_eq!(Ok(child_sk), SecretKey::derive_path(&seed, &path));
+        }
+    }
+
+    #[test]
+    fn test_derive_path() {
+        let seed = [7u8; 32];
+        let sk = SecretKey::derive_path(seed, "m/12381/3600/0/0").expect("Failed to derive key");
+        let mut expected = SecretKey::derive_master(seed).expect("Failed to derive master key");
+        for &index in &[12381, 3600, 0, 0] {
+            expected = expected
+                .derive_child(index)
+                .expect("Failed to derive child");
+        }
+        assert_eq!(expected, sk);
+        assert_eq!(
+            Ok(SecretKey::derive_master(seed).expect("Failed to derive master key")),
+            SecretKey::derive_path(seed, "m")
+        );
+
+        for path in &[
+            "",
+            "n/0",
+            "m/",
+            "m/-1",
+            "m/+1",
+            "m/4294967296",
+            "m/1/x",
+            "0/1",
+        ] {
+            assert_eq!(
+                Err(Error::InvalidDerivationPath),
+                SecretKey::derive_path(seed, path)
+            );
+        }
+        assert_eq!(
+            Err(Error::SeedTooShort),
+            SecretKey::derive_master([7u8; 31])
+        );
+    }
+}
@@ -0,0 +1,612 @@
+//! A synchronous distributed key generation protocol.
+//!
+//! A set of nodes, each with its own `SecretKey` and knowing everyone's `PublicKey`, can use this
+//! to generate a `PublicKeySet` and a `SecretKeyShare` for each node, without any trusted dealer:
+//! No coalition of up to `threshold` nodes learns the master secret key.
+//!
+//! The protocol is transport-agnostic, but it assumes that all nodes handle the same messages in
+//! the same order, e.g. because they are all passed through a consensus algorithm or a reliable
+//! broadcast. Each node's own messages have to be handled by itself, too.
+//!
+//! 1. Every node creates a `SyncKeyGen` instance, which returns a `Part`: a random symmetric
+//!    bivariate polynomial `f` of degree `threshold`, committed to as a `BivarCommitment`, and
+//!    the row `f(i + 1, _)` for each node `i`, encrypted to that node's public key.
+//! 2. For every `Part` from proposer `p`, each node `i` decrypts its row and verifies it against
+//!    the commitment. If it is valid, it responds with an `Ack`, containing the values
+//!    `f(i + 1, j + 1)` for each node `j`, encrypted to that node's public key.
+//! 3. For every `Ack` from node `i`, each node `j` decrypts its value and verifies it against the
+//!    commitment.
+//!
+//! A `Part` is _complete_ if it has more than `2 * threshold` acks: Then at least `threshold + 1`
+//! of them are from correct nodes, so every correct node can interpolate its row's value at `0`.
+//! As soon as more than `threshold` parts are complete, at least one of them is from a correct
+//! node, and everyone can call `generate`: The master key is the sum of the complete parts'
+//! `f(0, 0)`, and node `j`'s share is the sum of their `f(0, j + 1)`.
+//!
+//! This tolerates up to `threshold` faulty nodes, if there are more than `3 * threshold` nodes in
+//! total. Their misbehavior is reported as a `PartFault` or `AckFault`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::mem::size_of_val;
+
+use memsec::memzero;
+use pairing::bls12_381::{Fr, FrRepr, G1Affine};
+use pairing::{CurveAffine, Field, PrimeField, PrimeFieldRepr};
+use rand::Rng;
+
+use super::error::Result;
+use super::poly::{BivarCommitment, BivarPoly, Poly};
+use super::{Ciphertext, PublicKey, PublicKeySet, SecretKey, SecretKeyShare};
+
+/// The number of bytes in the big-endian representation of a field element.
+const FR_SIZE: usize = 32;
+
+/// A node's contribution: a commitment to a bivariate polynomial, and a row for each node,
+/// encrypted to that node's public key.
+#[derive(Deserialize, Serialize, Clone, Hash, Eq, PartialEq, Debug)]
+pub struct Part(BivarCommitment, Vec<Ciphertext>);
+
+/// A confirmation that a node has received and verified a `Part`. It contains the proposer's
+/// index, and the values of the node's row for each node, encrypted to that node's public key.
+#[derive(Deserialize, Serialize, Clone, Hash, Eq, PartialEq, Debug)]
+pub struct Ack(u64, Vec<Ciphertext>);
+
+/// The result of handling a `Part`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum PartOutcome {
+    /// The part was valid. Unless we are an observer, the `Ack` has to be sent to all nodes.
+    Valid(Option<Ack>),
+    /// The part was invalid.
+    Invalid(PartFault),
+}
+
+/// The result of handling an `Ack`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum AckOutcome {
+    /// The ack was valid.
+    Valid,
+    /// The ack was invalid.
+    Invalid(AckFault),
+}
+
+/// A faulty `Part`.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Fail)]
+pub enum PartFault {
+    #[fail(display = "Part sender is not a node")]
+    UnknownSender,
+    #[fail(display = "Part commitment has the wrong degree")]
+    Degree,
+    #[fail(display = "Part has the wrong number of rows")]
+    RowCount,
+    #[fail(display = "Received multiple parts from the same node")]
+    MultipleParts,
+    #[fail(display = "Could not decrypt our row")]
+    DecryptRow,
+    #[fail(display = "Could not deserialize our row")]
+    DeserializeRow,
+    #[fail(display = "Our row does not match the commitment")]
+    RowCommitment,
+}
+
+/// A faulty `Ack`.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Fail)]
+pub enum AckFault {
+    #[fail(display = "Ack sender is not a node")]
+    UnknownSender,
+    #[fail(display = "Ack has the wrong number of values")]
+    ValueCount,
+    #[fail(display = "No part was received from the ack's proposer")]
+    MissingPart,
+    #[fail(display = "Received multiple acks from the same node for the same part")]
+    DuplicateAck,
+    #[fail(display = "Could not decrypt our value")]
+    DecryptValue,
+    #[fail(display = "Could not deserialize our value")]
+    DeserializeValue,
+    #[fail(display = "Our value does not match the commitment")]
+    ValueCommitment,
+}
+
+/// The state of a single node's `Part`.
+struct ProposalState {
+    /// The proposer's commitment.
+    commit: BivarCommitment,
+    /// The verified values we received from `Ack` messages, by sender index plus one. They are
+    /// overwritten with zeros on drop.
+    values: BTreeMap<u64, Fr>,
+    /// The nodes which have acked this part, valid or not.
+    acks: BTreeSet<u64>,
+}
+
+impl ProposalState {
+    /// Creates a new part state with a commitment.
+    fn new(commit: BivarCommitment) -> ProposalState {
+        ProposalState {
+            commit,
+            values: BTreeMap::new(),
+            acks: BTreeSet::new(),
+        }
+    }
+
+    /// Returns `true` if at least `2 * threshold + 1` nodes have acked.
+    fn is_complete(&self, threshold: usize) -> bool {
+        self.acks.len() > 2 * threshold
+    }
+}
+
+impl Drop for ProposalState {
+    fn drop(&mut self) {
+        for val in self.values.values_mut() {
+            unsafe {
+                memzero(val as *mut Fr as *mut u8, size_of_val(val));
+            }
+        }
+    }
+}
+
+/// A synchronous key generation state machine. See the module documentation for details.
+pub struct SyncKeyGen<N> {
+    /// Our node ID.
+    our_id: N,
+    /// Our node index, or `None` if we are only an observer.
+    our_idx: Option<u64>,
+    /// Our secret key.
+    sec_key: SecretKey,
+    /// The public keys of all nodes, by node ID.
+    pub_keys: BTreeMap<N, PublicKey>,
+    /// Proposed bivariate polynomials, by proposer index.
+    parts: BTreeMap<u64, ProposalState>,
+    /// The degree of the generated polynomial.
+    threshold: usize,
+}
+
+impl<N: fmt::Debug> fmt::Debug for SyncKeyGen<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "SyncKeyGen {{ our_id: {:?}, threshold: {}, parts: {}, .. }}",
+            self.our_id,
+            self.threshold,
+            self.parts.len()
+        )
+    }
+}
+
+impl<N: Ord + Clone> SyncKeyGen<N> {
+    /// Creates a new key generation instance, together with the `Part` message that should be
+    /// sent to all nodes. If we are not one of the nodes, we are only an observer: We can compute
+    /// the `PublicKeySet`, but no `Part` is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn new<R: Rng>(
+        our_id: N,
+        sec_key: SecretKey,
+        pub_keys: BTreeMap<N, PublicKey>,
+        threshold: usize,
+        rng: &mut R,
+    ) -> Result<(SyncKeyGen<N>, Option<Part>)> {
+        let our_idx = pub_keys
+            .keys()
+            .position(|id| *id == our_id)
+            .map(|idx| idx as u64);
+        let key_gen = SyncKeyGen {
+            our_id,
+            our_idx,
+            sec_key,
+            pub_keys,
+            parts: BTreeMap::new(),
+            threshold,
+        };
+        if our_idx.is_none() {
+            return Ok((key_gen, None));
+        }
+        let our_part = BivarPoly::random(threshold, rng)?;
+        let commit = our_part.commitment();
+        let mut rows = Vec::with_capacity(key_gen.pub_keys.len());
+        for (i, pk) in key_gen.pub_keys.values().enumerate() {
+            let row = our_part.row(i + 1)?;
+            let mut bytes = write_frs(&row.coeff);
+            rows.push(pk.encrypt(&bytes));
+            clear(&mut bytes);
+        }
+        Ok((key_gen, Some(Part(commit, rows))))
+    }
+
+    /// Returns our node ID.
+    pub fn our_id(&self) -> &N {
+        &self.our_id
+    }
+
+    /// Returns the threshold of the key set that is being generated.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Handles a `Part` message. If it is valid and we are one of the nodes, returns an `Ack`
+    /// message that should be sent to all nodes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn handle_part(&mut self, sender_id: &N, part: Part) -> Result<PartOutcome> {
+        let Part(commit, rows) = part;
+        let sender_idx = match self.node_index(sender_id) {
+            Some(idx) => idx,
+            None => return Ok(PartOutcome::Invalid(PartFault::UnknownSender)),
+        };
+        if commit.degree() != self.threshold || !commit.is_well_formed() {
+            return Ok(PartOutcome::Invalid(PartFault::Degree));
+        }
+        if rows.len() != self.pub_keys.len() {
+            return Ok(PartOutcome::Invalid(PartFault::RowCount));
+        }
+        if self.parts.contains_key(&sender_idx) {
+            return Ok(PartOutcome::Invalid(PartFault::MultipleParts));
+        }
+        // Even if our own row turns out to be invalid, the part can still be completed by the
+        // other nodes' acks, so all nodes must record it.
+        let commit_row = commit.row(self.our_idx.map_or(0, |idx| idx + 1));
+        self.parts.insert(sender_idx, ProposalState::new(commit));
+        let our_idx = match self.our_idx {
+            Some(idx) => idx,
+            None => return Ok(PartOutcome::Valid(None)), // We are only an observer.
+        };
+        let mut bytes = match self.sec_key.decrypt(&rows[our_idx as usize]) {
+            Some(bytes) => bytes,
+            None => return Ok(PartOutcome::Invalid(PartFault::DecryptRow)),
+        };
+        let coeff = read_frs(&bytes);
+        clear(&mut bytes);
+        // The coefficients are moved into the row without copying, and zeroed when it is dropped.
+        let row = match coeff {
+            Some(coeff) if coeff.len() == self.threshold + 1 => Poly::new(coeff)?,
+            Some(mut coeff) => {
+                clear_frs(&mut coeff);
+                return Ok(PartOutcome::Invalid(PartFault::DeserializeRow));
+            }
+            None => return Ok(PartOutcome::Invalid(PartFault::DeserializeRow)),
+        };
+        if row.commitment() != commit_row {
+            return Ok(PartOutcome::Invalid(PartFault::RowCommitment));
+        }
+        let values = self
+            .pub_keys
+            .values()
+            .enumerate()
+            .map(|(j, pk)| {
+                let mut bytes = write_frs(&[row.evaluate(j + 1)]);
+                let ct = pk.encrypt(&bytes);
+                clear(&mut bytes);
+                ct
+            })
+            .collect();
+        Ok(PartOutcome::Valid(Some(Ack(sender_idx, values))))
+    }
+
+    /// Handles an `Ack` message.
+    pub fn handle_ack(&mut self, sender_id: &N, ack: Ack) -> AckOutcome {
+        let Ack(proposer_idx, values) = ack;
+        let sender_idx = match self.node_index(sender_id) {
+            Some(idx) => idx,
+            None => return AckOutcome::Invalid(AckFault::UnknownSender),
+        };
+        if values.len() != self.pub_keys.len() {
+            return AckOutcome::Invalid(AckFault::ValueCount);
+        }
+        let part = match self.parts.get_mut(&proposer_idx) {
+            Some(part) => part,
+            None => return AckOutcome::Invalid(AckFault::MissingPart),
+        };
+        // Like the part, the ack counts towards completion for all nodes, even if our own value
+        // is invalid.
+        if !part.acks.insert(sender_idx) {
+            return AckOutcome::Invalid(AckFault::DuplicateAck);
+        }
+        let our_idx = match self.our_idx {
+            Some(idx) => idx,
+            None => return AckOutcome::Valid, // We are only an observer.
+        };
+        let mut bytes = match self.sec_key.decrypt(&values[our_idx as usize]) {
+            Some(bytes) => bytes,
+            None => return AckOutcome::Invalid(AckFault::DecryptValue),
+        };
+        let value = read_frs(&bytes);
+        clear(&mut bytes);
+        let val = match value {
+            Some(mut val) => {
+                let opt_val = if val.len() == 1 { Some(val[0]) } else { None };
+                clear_frs(&mut val);
+                match opt_val {
+                    Some(val) => val,
+                    None => return AckOutcome::Invalid(AckFault::DeserializeValue),
+                }
+            }
+            None => return AckOutcome::Invalid(AckFault::DeserializeValue),
+        };
+        if part.commit.evaluate(sender_idx + 1, our_idx + 1) != G1Affine::one().mul(val) {
+            return AckOutcome::Invalid(AckFault::ValueCommitment);
+        }
+        part.values.insert(sender_idx + 1, val);
+        AckOutcome::Valid
+    }
+
+    /// Returns the number of complete parts, i.e. parts that have been acked by more than
+    /// `2 * threshold` nodes.
+    pub fn count_complete(&self) -> usize {
+        self.parts
+            .values()
+            .filter(|part| part.is_complete(self.threshold))
+            .count()
+    }
+
+    /// Returns `true` if the part of the given node is complete.
+    pub fn is_node_ready(&self, proposer_id: &N) -> bool {
+        self.node_index(proposer_id)
+            .and_then(|proposer_idx| self.parts.get(&proposer_idx))
+            .filter(|part| part.is_complete(self.threshold))
+            .is_some()
+    }
+
+    /// Returns `true` if more than `threshold` parts are complete, so that the keys can be
+    /// generated.
+    pub fn is_ready(&self) -> bool {
+        self.count_complete() > self.threshold
+    }
+
+    /// Returns the new public key set, and our secret key share unless we are an observer.
+    ///
+    /// All correct nodes that have handled the same messages obtain the same public key set. This
+    /// should only be called once `is_ready` returns `true`: Otherwise the master key could be
+    /// known to a faulty node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::NotEnoughShares` if we didn't receive enough valid values for one of the
+    /// complete parts, which can only happen if more than `threshold` nodes are faulty, and an
+    /// `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn generate(&self) -> Result<(PublicKeySet, Option<SecretKeyShare>)> {
+        let mut pk_commit = Poly::zero()?.commitment();
+        let mut opt_sk_val = self.our_idx.map(|_| Fr::zero());
+        let is_complete = |part: &&ProposalState| part.is_complete(self.threshold);
+        for part in self.parts.values().filter(is_complete) {
+            pk_commit += part.commit.row(0);
+            if let Some(sk_val) = opt_sk_val.as_mut() {
+                if part.values.len() <= self.threshold {
+                    return Err(super::error::Error::NotEnoughShares);
+                }
+                let row = Poly::interpolate(part.values.iter().take(self.threshold + 1))?;
+                sk_val.add_assign(&row.evaluate(0));
+            }
+        }
+        let opt_sk = match opt_sk_val {
+            Some(mut fr) => Some(SecretKeyShare::from_mut_ptr(&mut fr as *mut Fr)?),
+            None => None,
+        };
+        Ok((pk_commit.into(), opt_sk))
+    }
+
+    /// Returns the index of the node, or `None` if it is unknown.
+    fn node_index(&self, node_id: &N) -> Option<u64> {
+        self.pub_keys
+            .keys()
+            .position(|id| id == node_id)
+            .map(|idx| idx as u64)
+    }
+}
+
+/// Returns the concatenated big-endian representations of the field elements.
+fn write_frs(frs: &[Fr]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(frs.len() * FR_SIZE);
+    for fr in frs {
+        fr.into_repr()
+            .write_be(&mut bytes)
+            .expect("writing to a vector never fails");
+    }
+    bytes
+}
+
+/// Returns the field elements with the given concatenated big-endian representations, or `None`
+/// if they are invalid. The vector is allocated once, so that no copies of the secret values are
+/// left behind; the caller is responsible for zeroing it.
+fn read_frs(bytes: &[u8]) -> Option<Vec<Fr>> {
+    let chunks = bytes.chunks_exact(FR_SIZE);
+    if !chunks.remainder().is_empty() {
+        return None;
+    }
+    let mut frs = Vec::with_capacity(chunks.len());
+    for chunk in chunks {
+        let mut repr = FrRepr::default();
+        let opt_fr = match repr.read_be(chunk) {
+            Ok(()) => Fr::from_repr(repr).ok(),
+            Err(_) => None,
+        };
+        match opt_fr {
+            Some(fr) => frs.push(fr),
+            None => {
+                clear_frs(&mut frs);
+                return None;
+            }
+        }
+    }
+    Some(frs)
+}
+
+/// Overwrites the secret bytes with zeros.
+fn clear(bytes: &mut Vec<u8>) {
+    unsafe {
+        memzero(bytes.as_mut_ptr(), bytes.len());
+    }
+}
+
+/// Overwrites the secret field elements with zeros.
+fn clear_frs(frs: &mut Vec<Fr>) {
+    unsafe {
+        memzero(frs.as_mut_ptr() as *mut u8, size_of_val(frs.as_slice()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rand::{self, random};
+
+    use super::*;
+
+    /// Creates `node_num` nodes, and returns their secret and public keys.
+    fn node_keys(node_num: usize) -> (Vec<SecretKey>, BTreeMap<usize, PublicKey>) {
+        let sec_keys: Vec<SecretKey> = (0..node_num).map(|_| random()).collect();
+        let pub_keys = sec_keys
+            .iter()
+            .map(SecretKey::public_key)
+            .enumerate()
+            .collect();
+        (sec_keys, pub_keys)
+    }
+
+    #[test]
+    fn test_sync_key_gen() {
+        let mut rng = rand::thread_rng();
+        let (threshold, node_num) = (1, 4);
+        let (sec_keys, pub_keys) = node_keys(node_num);
+
+        // Create the key generation instances, and the observer with ID `node_num`.
+        let mut nodes = Vec::new();
+        let mut parts = Vec::new();
+        for (id, sk) in sec_keys.into_iter().enumerate() {
+            let (node, part) = SyncKeyGen::new(id, sk, pub_keys.clone(), threshold, &mut rng)
+                .expect("Failed to create `SyncKeyGen`");
+            nodes.push(node);
+            parts.push((id, part.expect("nodes create a part")));
+        }
+        let (mut observer, part) =
+            SyncKeyGen::new(node_num, random(), pub_keys.clone(), threshold, &mut rng)
+                .expect("Failed to create `SyncKeyGen`");
+        assert!(part.is_none());
+
+        // Node 3 is faulty: it sends garbage to node 0, and never acks anything.
+        let (_, ref mut faulty_part) = parts[3];
+        faulty_part.1[0] = pub_keys[&0].encrypt(b"garbage");
+
+        // All nodes handle all parts, in the same order.
+        let mut acks = Vec::new();
+        for (sender_id, part) in parts {
+            for node in &mut nodes {
+                match node
+                    .handle_part(&sender_id, part.clone())
+                    .expect("Failed to handle part")
+                {
+                    PartOutcome::Valid(Some(ack)) => {
+                        if *node.our_id() != 3 {
+                            acks.push((*node.our_id(), ack));
+                        }
+                    }
+                    PartOutcome::Valid(None) => panic!("nodes should ack"),
+                    PartOutcome::Invalid(fault) => {
+                        assert_eq!((0, 3), (*node.our_id(), sender_id));
+                        assert_eq!(PartFault::DeserializeRow, fault);
+                    }
+                }
+            }
+            let outcome = observer.handle_part(&sender_id, part).expect("observer");
+            assert_eq!(PartOutcome::Valid(None), outcome);
+        }
+
+        // All nodes handle all acks.
+        for (sender_id, ack) in acks {
+            for node in &mut nodes {
+                assert_eq!(AckOutcome::Valid, node.handle_ack(&sender_id, ack.clone()));
+            }
+            assert_eq!(AckOutcome::Valid, observer.handle_ack(&sender_id, ack));
+        }
+
+        // Node 3's part has only two acks, which is not enough. All other parts are complete.
+        assert!(!nodes[0].is_node_ready(&3));
+        assert!(nodes[0].is_node_ready(&1));
+        assert_eq!(3, nodes[0].count_complete());
+        assert!(nodes.iter().all(SyncKeyGen::is_ready));
+        assert!(observer.is_ready());
+
+        // Everyone obtains the same public key set, and the shares match it.
+        let (pub_key_set, none) = observer.generate().expect("Failed to generate keys");
+        assert!(none.is_none());
+        let msg = "Nodes 0 and 1 sign this.";
+        let sig_shares: BTreeMap<_, _> = nodes
+            .iter()
+            .map(|node| {
+                let (pks, sks) = node.generate().expect("Failed to generate keys");
+                assert_eq!(pub_key_set, pks);
+                let sks = sks.expect("nodes get a share");
+                let id = *node.our_id();
+                assert_eq!(pks.public_key_share(id), sks.public_key_share());
+                (id, sks.sign(msg))
+            })
+            .take(threshold + 1)
+            .collect();
+        let sig = pub_key_set
+            .combine_signatures(&sig_shares)
+            .expect("Failed to combine signatures");
+        assert!(pub_key_set.public_key().verify(&sig, msg));
+    }
+
+    #[test]
+    fn test_faulty_messages() {
+        let mut rng = rand::thread_rng();
+        let (sec_keys, pub_keys) = node_keys(4);
+        let mut sec_keys = sec_keys.into_iter();
+        let sk0 = sec_keys.next().expect("node 0");
+        let sk1 = sec_keys.next().expect("node 1");
+        let (mut node0, part0) =
+            SyncKeyGen::new(0, sk0, pub_keys.clone(), 1, &mut rng).expect("node 0");
+        let (mut node1, part1) =
+            SyncKeyGen::new(1, sk1, pub_keys.clone(), 1, &mut rng).expect("node 1");
+        let (part0, part1) = (part0.expect("part 0"), part1.expect("part 1"));
+
+        // Unknown senders, wrong degrees and wrong row counts are rejected.
+        let outcome = node0.handle_part(&5, part1.clone()).expect("handle part");
+        assert_eq!(PartOutcome::Invalid(PartFault::UnknownSender), outcome);
+        let (_, wrong_degree) = SyncKeyGen::new(1, random(), pub_keys.clone(), 2, &mut rng)
+            .expect("Failed to create `SyncKeyGen`");
+        let outcome = node0
+            .handle_part(&1, wrong_degree.expect("part"))
+            .expect("handle part");
+        assert_eq!(PartOutcome::Invalid(PartFault::Degree), outcome);
+        let mut wrong_rows = part1.clone();
+        wrong_rows.1.pop();
+        let outcome = node0.handle_part(&1, wrong_rows).expect("handle part");
+        assert_eq!(PartOutcome::Invalid(PartFault::RowCount), outcome);
+
+        // A row that doesn't match the commitment is detected.
+        let mut wrong_row = part1.clone();
+        let random_row: Vec<Fr> = (0..2).map(|_| rng.gen()).collect();
+        wrong_row.1[0] = pub_keys[&0].encrypt(write_frs(&random_row));
+        let outcome = node0.handle_part(&1, wrong_row).expect("handle part");
+        assert_eq!(PartOutcome::Invalid(PartFault::RowCommitment), outcome);
+        // The part has been recorded nevertheless.
+        let outcome = node0.handle_part(&1, part1.clone()).expect("handle part");
+        assert_eq!(PartOutcome::Invalid(PartFault::MultipleParts), outcome);
+
+        // Acks for unknown parts, duplicate acks and wrong values are detected.
+        let ack = match node1.handle_part(&0, part0).expect("handle part") {
+            PartOutcome::Valid(Some(ack)) => ack,
+            outcome => panic!("unexpected outcome {:?}", outcome),
+        };
+        assert_eq!(AckOutcome::Valid, node1.handle_ack(&1, ack.clone()));
+        assert_eq!(
+            AckOutcome::Invalid(AckFault::DuplicateAck),
+            node1.handle_ack(&1, ack.clone())
+        );
+        assert_eq!(
+            AckOutcome::Invalid(AckFault::MissingPart),
+            node0.handle_ack(&1, Ack(3, ack.1.clone()))
+        );
+        // Node 2 can't claim node 1's values as its own.
+        assert_eq!(
+            AckOutcome::Invalid(AckFault::ValueCommitment),
+            node1.handle_ack(&2, ack)
+        );
+    }
+}
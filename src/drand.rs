@@ -0,0 +1,146 @@
+//! Compatibility with the [drand](https://drand.love) randomness beacon.
+//!
+//! drand uses the same curve and the same key layout as this crate: Public keys are elements of
+//! `G1`, signatures are elements of `G2`, and both are encoded in the same compressed format. It
+//! differs in two respects:
+//!
+//! * Messages are hashed to `G2` as specified in the IETF hash-to-curve draft, with the ciphersuite
+//!   `BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_`.
+//! * The message for round `r` is the SHA-256 hash of the previous signature (in chained mode)
+//!   followed by `r` as a big-endian 64-bit integer.
+//!
+//! `verify_entry` checks drand's public entries against its group public key, and a
+//! `Beacon::new_drand` produces entries that drand clients can verify. drand derives a round's
+//! randomness with SHA-256 instead of SHA3-256; see `randomness`.
+
+use byteorder::{BigEndian, ByteOrder};
+use pairing::bls12_381::G2;
+use sha2::{Digest, Sha256};
+
+use super::beacon::BeaconEntry;
+use super::{hash_to_g2, PublicKey};
+
+/// The domain separation tag of drand's hash-to-curve ciphersuite.
+pub const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+
+/// Returns the hash of the given message in `G2`, using drand's ciphersuite.
+pub fn hash_g2<M: AsRef<[u8]>>(msg: M) -> G2 {
+    hash_to_g2(msg, DST)
+}
+
+/// Returns drand's message for the given round: the SHA-256 hash of the previous signature, if
+/// any, and the round number.
+pub fn round_message(round: u64, previous_signature: Option<&[u8]>) -> Vec<u8> {
+    let mut round_bytes = [0; 8];
+    BigEndian::write_u64(&mut round_bytes, round);
+    let mut hasher = Sha256::new();
+    hasher.update(previous_signature.unwrap_or(&[]));
+    hasher.update(round_bytes);
+    hasher.finalize().to_vec()
+}
+
+/// Returns `true` if the entry was signed by drand's group public key `pk`. Entries without a
+/// previous signature are verified as unchained.
+pub fn verify_entry(pk: &PublicKey, entry: &BeaconEntry) -> bool {
+    let msg = round_message(entry.round, entry.previous_signature.as_deref());
+    pk.verify_g2(&entry.signature, hash_g2(msg))
+}
+
+/// Returns the randomness of an entry in drand's format: the SHA-256 hash of the signature.
+pub fn randomness(entry: &BeaconEntry) -> [u8; 32] {
+    let mut randomness = [0; 32];
+    randomness.copy_from_slice(&Sha256::digest(&entry.signature.to_bytes()));
+    randomness
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rand;
+
+    use super::super::beacon::{Beacon, BeaconMode};
+    use super::super::{SecretKeySet, Signature};
+    use super::*;
+
+    /// The public key of drand's mainnet chained beacon.
+    const MAINNET_PUBLIC_KEY: &str = concat!(
+        "868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a5",
+        "69937c529eeda66c7293784a9402801af31",
+    );
+
+    /// Round `72785` of drand's mainnet chained beacon.
+    const MAINNET_ROUND: u64 = 72785;
+    const MAINNET_SIGNATURE: &str = concat!(
+        "82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959",
+        "eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae",
+        "86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42",
+    );
+    const MAINNET_PREVIOUS_SIGNATURE: &str = concat!(
+        "a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8",
+        "a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182",
+        "554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747",
+    );
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..(i + 2)], 16).expect("valid hex"))
+            .collect()
+    }
+
+    fn mainnet_entry() -> BeaconEntry {
+        BeaconEntry {
+            round: MAINNET_ROUND,
+            signature: Signature::from_bytes(from_hex(MAINNET_SIGNATURE)).expect("signature"),
+            previous_signature: Some(from_hex(MAINNET_PREVIOUS_SIGNATURE)),
+        }
+    }
+
+    #[test]
+    fn test_verify_mainnet_entry() {
+        let pk = PublicKey::from_bytes(from_hex(MAINNET_PUBLIC_KEY)).expect("public key");
+        let entry = mainnet_entry();
+        assert!(verify_entry(&pk, &entry));
+
+        // The same entry claiming a different round, or a tampered chain, is invalid.
+        let mut wrong_round = entry.clone();
+        wrong_round.round += 1;
+        assert!(!verify_entry(&pk, &wrong_round));
+        let mut wrong_chain = entry.clone();
+        wrong_chain.previous_signature = Some(entry.signature.to_bytes());
+        assert!(!verify_entry(&pk, &wrong_chain));
+        let mut unchained = entry;
+        unchained.previous_signature = None;
+        assert!(!verify_entry(&pk, &unchained));
+    }
+
+    #[test]
+    fn test_produce_drand_entries() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng).expect("Failed to create `SecretKeySet`");
+        let pk_set = sk_set.public_keys();
+        for &mode in &[BeaconMode::Chained, BeaconMode::Unchained] {
+            let mut beacon = Beacon::new_drand(pk_set.clone(), mode, b"genesis".to_vec());
+            let shares: BTreeMap<_, _> = (0..2)
+                .map(|i| {
+                    let sk_share = sk_set
+                        .secret_key_share(i)
+                        .unwrap_or_else(|_| panic!("Failed to create `SecretKeyShare` #{}", i));
+                    (i, beacon.sign_share(i, &sk_share))
+                })
+                .collect();
+            let mut entries = shares
+                .values()
+                .flat_map(|share| beacon.handle_share(share.clone()).expect("valid share"));
+            let entry = entries.next().expect("round complete");
+            // A drand client only needs the group public key.
+            assert!(verify_entry(&pk_set.public_key(), &entry));
+            assert_ne!(entry.randomness(), randomness(&entry));
+            assert_eq!(
+                mode == BeaconMode::Chained,
+                entry.previous_signature.is_some()
+            );
+        }
+    }
+}
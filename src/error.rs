@@ -9,6 +9,26 @@ pub enum Error {
     NotEnoughShares,
     #[fail(display = "Signature shares contain a duplicated index")]
     DuplicateEntry,
+    #[fail(display = "Share is invalid")]
+    InvalidShare,
+    #[fail(display = "Beacon entry is invalid")]
+    InvalidBeaconEntry,
+    #[fail(display = "Invalid byte representation")]
+    InvalidBytes,
+    #[fail(display = "Zero-knowledge proof is invalid")]
+    InvalidProof,
+    #[fail(display = "Wrong passphrase, or the keystore has been modified")]
+    InvalidPassword,
+    #[fail(display = "Keystore is malformed or uses an unsupported format")]
+    InvalidKeystore,
+    #[fail(display = "Seed is too short")]
+    SeedTooShort,
+    #[fail(display = "Invalid key derivation path")]
+    InvalidDerivationPath,
+    #[fail(display = "Invalid mnemonic phrase")]
+    InvalidMnemonic,
+    #[fail(display = "Signature is invalid")]
+    InvalidSignature,
     #[fail(
         display = "Failed to `mlock` {} bytes starting at address: {}",
         n_bytes,
@@ -0,0 +1,665 @@
+//! Passphrase-encrypted keystore files for secret keys and secret key shares.
+//!
+//! The JSON layout follows [EIP-2335](https://eips.ethereum.org/EIPS/eip-2335): The passphrase is
+//! stretched into a decryption key with scrypt or PBKDF2, half of which encrypts the secret with
+//! AES-128-CTR, while the other half is hashed with the ciphertext into a checksum. A wrong
+//! passphrase, or any modification of the ciphertext, makes the checksum fail.
+//!
+//! Keystores for secret key shares contain an additional `share` field with the share's index, the
+//! key set's threshold and a hash of its `PublicKeySet`, so that a share is not accidentally used
+//! with the wrong key set.
+
+use std::fmt::Write;
+
+use aes::cipher::{NewCipher, StreamCipher};
+use aes::Aes128Ctr;
+use hex;
+use hmac::Hmac;
+use memsec::memzero;
+use pairing::{CurveAffine, CurveProjective};
+use pbkdf2::pbkdf2;
+use rand::Rng;
+use scrypt::{scrypt, Params as ScryptParams};
+use serde_json;
+use sha2::{Digest, Sha256};
+use unicode_normalization::UnicodeNormalization;
+
+use super::error::{Error, Result};
+use super::{PublicKey, PublicKeySet, SecretKey, SecretKeyShare, SK_SIZE};
+
+/// The keystore format version.
+const VERSION: u32 = 4;
+/// The length of the key derived from the passphrase.
+const DK_LEN: usize = 32;
+/// The maximum scrypt cost `n * r * p`. This bounds scrypt's memory usage of `128 * n * r` bytes
+/// to 1 GiB, and its running time to four times that of the default parameters, so that a
+/// malicious keystore file can't make decryption exhaust the system's resources.
+const MAX_SCRYPT_COST: u64 = 1 << 23;
+/// The maximum number of PBKDF2 iterations, 16 times the number used by EIP-2335.
+const MAX_PBKDF2_C: u32 = 1 << 22;
+
+/// The key derivation function that stretches the passphrase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kdf {
+    /// scrypt with the cost parameter `n = 2^log_n`, the block size `r` and parallelism `p`.
+    Scrypt { log_n: u8, r: u32, p: u32 },
+    /// PBKDF2 with HMAC-SHA256 and `c` iterations.
+    Pbkdf2 { c: u32 },
+}
+
+/// The default are the scrypt parameters recommended by EIP-2335.
+impl Default for Kdf {
+    fn default() -> Self {
+        Kdf::Scrypt {
+            log_n: 18,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+/// The metadata of a keystore for a secret key share.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ShareInfo {
+    /// The share's index.
+    index: u64,
+    /// The key set's threshold.
+    threshold: usize,
+    /// The SHA-256 hash of the key set's commitment, hex-encoded.
+    public_key_set_hash: String,
+}
+
+impl ShareInfo {
+    /// Returns the share's index.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Returns the key set's threshold.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Returns `true` if the share belongs to the given key set.
+    pub fn matches(&self, pk_set: &PublicKeySet) -> bool {
+        self.threshold == pk_set.threshold() && self.public_key_set_hash == hash_pk_set(pk_set)
+    }
+}
+
+/// An EIP-2335 keystore, containing an encrypted secret key or secret key share.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Keystore {
+    crypto: Crypto,
+    description: String,
+    pubkey: String,
+    path: String,
+    uuid: String,
+    version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    share: Option<ShareInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct Crypto {
+    kdf: Module<KdfParams>,
+    checksum: Module<EmptyParams>,
+    cipher: Module<CipherParams>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct Module<P> {
+    function: String,
+    params: P,
+    message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+enum KdfParams {
+    Scrypt {
+        dklen: usize,
+        n: u32,
+        r: u32,
+        p: u32,
+        salt: String,
+    },
+    Pbkdf2 {
+        dklen: usize,
+        c: u32,
+        prf: String,
+        salt: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct EmptyParams {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct CipherParams {
+    iv: String,
+}
+
+impl Keystore {
+    /// Encrypts the secret key under the passphrase.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidKeystore` if the key derivation parameters are invalid or exceed
+    /// the maximum cost.
+    pub fn encrypt<R: Rng>(sk: &SecretKey, password: &str, kdf: Kdf, rng: &mut R) -> Result<Self> {
+        let salt = hex::encode(rng.gen::<[u8; 32]>());
+        let kdf_params = match kdf {
+            Kdf::Scrypt { log_n, r, p } => KdfParams::Scrypt {
+                dklen: DK_LEN,
+                n: 1u32
+                    .checked_shl(u32::from(log_n))
+                    .ok_or(Error::InvalidKeystore)?,
+                r,
+                p,
+                salt,
+            },
+            Kdf::Pbkdf2 { c } => KdfParams::Pbkdf2 {
+                dklen: DK_LEN,
+                c,
+                prf: "hmac-sha256".to_string(),
+                salt,
+            },
+        };
+        let iv: [u8; 16] = rng.gen();
+        let mut dk = derive_key(&kdf_params, password)?;
+        let mut bytes = [0u8; SK_SIZE];
+        sk.to_bytes(&mut bytes);
+        Aes128Ctr::new_from_slices(&dk[..16], &iv)
+            .expect("key and IV have the correct lengths")
+            .apply_keystream(&mut bytes);
+        let checksum = checksum(&dk, &bytes);
+        unsafe {
+            memzero(dk.as_mut_ptr(), dk.len());
+        }
+        let function = match kdf {
+            Kdf::Scrypt { .. } => "scrypt",
+            Kdf::Pbkdf2 { .. } => "pbkdf2",
+        };
+        Ok(Keystore {
+            crypto: Crypto {
+                kdf: Module {
+                    function: function.to_string(),
+                    params: kdf_params,
+                    message: String::new(),
+                },
+                checksum: Module {
+                    function: "sha256".to_string(),
+                    params: EmptyParams {},
+                    message: hex::encode(checksum),
+                },
+                cipher: Module {
+                    function: "aes-128-ctr".to_string(),
+                    params: CipherParams {
+                        iv: hex::encode(iv),
+                    },
+                    message: hex::encode(bytes),
+                },
+            },
+            description: String::new(),
+            pubkey: hex::encode(sk.public_key().to_bytes()),
+            path: String::new(),
+            uuid: random_uuid(rng),
+            version: VERSION,
+            share: None,
+        })
+    }
+
+    /// Encrypts the `index`-th secret key share of the given key set under the passphrase.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidShare` if the share doesn't belong to the key set, or an
+    /// `Error::InvalidKeystore` if the key derivation parameters are invalid.
+    pub fn encrypt_share<R: Rng>(
+        share: &SecretKeyShare,
+        index: u64,
+        pk_set: &PublicKeySet,
+        password: &str,
+        kdf: Kdf,
+        rng: &mut R,
+    ) -> Result<Self> {
+        if share.public_key_share() != pk_set.public_key_share(index) {
+            return Err(Error::InvalidShare);
+        }
+        let mut keystore = Keystore::encrypt(&share.0, password, kdf, rng)?;
+        keystore.share = Some(ShareInfo {
+            index,
+            threshold: pk_set.threshold(),
+            public_key_set_hash: hash_pk_set(pk_set),
+        });
+        Ok(keystore)
+    }
+
+    /// Decrypts the secret key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidPassword` if the passphrase is wrong or the ciphertext has been
+    /// modified, or an `Error::InvalidKeystore` if the keystore is malformed, or the decrypted key
+    /// doesn't match the public key.
+    pub fn decrypt(&self, password: &str) -> Result<SecretKey> {
+        let crypto = &self.crypto;
+        let kdf_function = match crypto.kdf.params {
+            KdfParams::Scrypt { .. } => "scrypt",
+            KdfParams::Pbkdf2 { .. } => "pbkdf2",
+        };
+        if self.version != VERSION
+            || crypto.kdf.function != kdf_function
+            || crypto.checksum.function != "sha256"
+            || crypto.cipher.function != "aes-128-ctr"
+        {
+            return Err(Error::InvalidKeystore);
+        }
+        let mut bytes = [0u8; SK_SIZE];
+        decode_hex(&crypto.cipher.message, &mut bytes)?;
+        let mut iv = [0u8; 16];
+        decode_hex(&crypto.cipher.params.iv, &mut iv)?;
+        let mut expected = [0u8; 32];
+        decode_hex(&crypto.checksum.message, &mut expected)?;
+
+        let mut dk = derive_key(&crypto.kdf.params, password)?;
+        let checksum = checksum(&dk, &bytes);
+        let diff = checksum
+            .iter()
+            .zip(&expected)
+            .fold(0, |acc, (a, b)| acc | (a ^ b));
+        if diff != 0 {
+            unsafe {
+                memzero(dk.as_mut_ptr(), dk.len());
+            }
+            return Err(Error::InvalidPassword);
+        }
+        Aes128Ctr::new_from_slices(&dk[..16], &iv)
+            .expect("key and IV have the correct lengths")
+            .apply_keystream(&mut bytes);
+        unsafe {
+            memzero(dk.as_mut_ptr(), dk.len());
+        }
+        let sk = SecretKey::from_bytes(&mut bytes).map_err(|_| Error::InvalidKeystore)?;
+        if Some(sk.public_key()) != self.public_key() {
+            return Err(Error::InvalidKeystore);
+        }
+        Ok(sk)
+    }
+
+    /// Decrypts the secret key share, and checks that it belongs to the given key set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidShare` if the share doesn't belong to the key set, or any of the
+    /// errors returned by `decrypt`.
+    pub fn decrypt_share(&self, password: &str, pk_set: &PublicKeySet) -> Result<SecretKeyShare> {
+        let info = self.share.as_ref().ok_or(Error::InvalidKeystore)?;
+        if !info.matches(pk_set) {
+            return Err(Error::InvalidShare);
+        }
+        let share = SecretKeyShare(self.decrypt(password)?);
+        if share.public_key_share() != pk_set.public_key_share(info.index) {
+            return Err(Error::InvalidShare);
+        }
+        Ok(share)
+    }
+
+    /// Returns the public key, or `None` if the keystore contains an invalid one.
+    pub fn public_key(&self) -> Option<PublicKey> {
+        let bytes = hex::decode(&self.pubkey).ok()?;
+        PublicKey::from_bytes(bytes).ok()
+    }
+
+    /// Returns the share metadata, if this keystore contains a secret key share.
+    pub fn share_info(&self) -> Option<&ShareInfo> {
+        self.share.as_ref()
+    }
+
+    /// Returns the description.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Sets the description, which can help users identify the keystore.
+    pub fn set_description<S: Into<String>>(&mut self, description: S) {
+        self.description = description.into();
+    }
+
+    /// Returns the derivation path of the key, or an empty string if it was not derived.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Sets the derivation path of the key.
+    pub fn set_path<S: Into<String>>(&mut self, path: S) {
+        self.path = path.into();
+    }
+
+    /// Returns the keystore's UUID.
+    pub fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    /// Returns the keystore in JSON format.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("keystore can be serialized")
+    }
+
+    /// Parses a keystore in JSON format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidKeystore` if the JSON is not a valid keystore.
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|_| Error::InvalidKeystore)
+    }
+}
+
+/// Stretches the passphrase into the decryption key.
+fn derive_key(params: &KdfParams, password: &str) -> Result<[u8; DK_LEN]> {
+    // The parameters are validated before the passphrase is normalized, so that it is zeroed on
+    // every path where it exists.
+    let (dklen, salt) = match *params {
+        KdfParams::Scrypt {
+            dklen,
+            n,
+            r,
+            p,
+            ref salt,
+        } => {
+            let cost = u64::from(n) * u64::from(r) * u64::from(p);
+            if !n.is_power_of_two() || cost > MAX_SCRYPT_COST {
+                return Err(Error::InvalidKeystore);
+            }
+            (dklen, salt)
+        }
+        KdfParams::Pbkdf2 {
+            dklen,
+            c,
+            ref prf,
+            ref salt,
+        } => {
+            if prf != "hmac-sha256" || c > MAX_PBKDF2_C {
+                return Err(Error::InvalidKeystore);
+            }
+            (dklen, salt)
+        }
+    };
+    if dklen != DK_LEN {
+        return Err(Error::InvalidKeystore);
+    }
+    let salt = hex::decode(salt).map_err(|_| Error::InvalidKeystore)?;
+    let mut password = normalize_password(password);
+    let mut dk = [0u8; DK_LEN];
+    let result = match *params {
+        KdfParams::Scrypt { n, r, p, .. } => ScryptParams::new(n.trailing_zeros() as u8, r, p)
+            .map_err(|_| Error::InvalidKeystore)
+            .map(|params| scrypt(&password, &salt, &params, &mut dk).expect("valid output length")),
+        KdfParams::Pbkdf2 { c, .. } => {
+            pbkdf2::<Hmac<Sha256>>(&password, &salt, c, &mut dk);
+            Ok(())
+        }
+    };
+    unsafe {
+        memzero(password.as_mut_ptr(), password.len());
+    }
+    result.map(|()| dk)
+}
+
+/// Returns the checksum of the ciphertext, with the second half of the decryption key.
+fn checksum(dk: &[u8; DK_LEN], ciphertext: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(&dk[16..]);
+    hasher.update(ciphertext);
+    hasher.finalize().to_vec()
+}
+
+/// Returns the NFKD-normalized passphrase, without control codes, as bytes.
+fn normalize_password(password: &str) -> Vec<u8> {
+    let is_control = |c: &char| matches!(*c as u32, 0x00..=0x1f | 0x7f..=0x9f);
+    let normalized: String = password.nfkd().filter(|c| !is_control(c)).collect();
+    normalized.into_bytes()
+}
+
+/// Decodes the hexadecimal string into the buffer, which must have exactly the right length.
+fn decode_hex(hex_str: &str, buf: &mut [u8]) -> Result<()> {
+    hex::decode_to_slice(hex_str, buf).map_err(|_| Error::InvalidKeystore)
+}
+
+/// Returns the hex-encoded SHA-256 hash of the key set's commitment.
+fn hash_pk_set(pk_set: &PublicKeySet) -> String {
+    let mut hasher = Sha256::new();
+    for c in &pk_set.commit.coeff {
+        hasher.update(c.into_affine().into_compressed().as_ref());
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Returns a random version 4 UUID.
+fn random_uuid<R: Rng>(rng: &mut R) -> String {
+    let mut bytes: [u8; 16] = rng.gen();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let mut uuid = String::with_capacity(36);
+    for (i, byte) in bytes.iter().enumerate() {
+        if i == 4 || i == 6 || i == 8 || i == 10 {
+            uuid.push('-');
+        }
+        write!(uuid, "{:02x}", byte).expect("writing to a string doesn't fail");
+    }
+    uuid
+}
+
+#[cfg(test)]
+mod tests {
+    use rand;
+
+    use super::super::SecretKeySet;
+    use super::*;
+
+    /// Cheap key derivation parameters, to keep the tests fast.
+    const TEST_KDFS: [Kdf; 2] = [
+        Kdf::Scrypt {
+            log_n: 4,
+            r: 8,
+            p: 1,
+        },
+        Kdf::Pbkdf2 { c: 16 },
+    ];
+
+    #[test]
+    fn test_keystore() {
+        let mut rng = rand::thread_rng();
+        let sk: SecretKey = rand::random();
+        for &kdf in &TEST_KDFS {
+            let mut keystore =
+                Keystore::encrypt(&sk, "correct horse", kdf, &mut rng).expect("encrypts");
+            keystore.set_description("test key");
+            let json = keystore.to_json();
+            let parsed = Keystore::from_json(&json).expect("valid keystore");
+            assert_eq!(keystore, parsed);
+            assert_eq!(Some(sk.public_key()), parsed.public_key());
+            assert_eq!(36, parsed.uuid().len());
+            assert_eq!(Ok(sk.clone()), parsed.decrypt("correct horse"));
+
+            // Control codes are ignored, and compatibility characters are normalized.
+            assert_eq!(Ok(sk.clone()), parsed.decrypt("correct\u{7f} horse"));
+            assert_eq!(
+                Err(Error::InvalidPassword),
+                parsed.decrypt("correct horse!")
+            );
+
+            // Modifications are detected.
+            let mut tampered = parsed.clone();
+            tampered.crypto.cipher.message.replace_range(0..2, "00");
+            tampered.crypto.cipher.message.replace_range(2..4, "ff");
+            assert_eq!(
+                Err(Error::InvalidPassword),
+                tampered.decrypt("correct horse")
+            );
+            let mut tampered = parsed.clone();
+            tampered.pubkey = hex::encode(rand::random::<SecretKey>().public_key().to_bytes());
+            assert_eq!(
+                Err(Error::InvalidKeystore),
+                tampered.decrypt("correct horse")
+            );
+            let mut tampered = parsed;
+            tampered.crypto.cipher.function = "aes-256-ctr".to_string();
+            assert_eq!(
+                Err(Error::InvalidKeystore),
+                tampered.decrypt("correct horse")
+            );
+        }
+        assert_eq!(Err(Error::InvalidKeystore), Keystore::from_json("{}"));
+    }
+
+    #[test]
+    fn test_kdf_limits() {
+        let mut rng = rand::thread_rng();
+        let sk: SecretKey = rand::random();
+
+        // Parameters that would take too much memory or time are rejected before deriving a key.
+        for &kdf in &[
+            Kdf::Scrypt {
+                log_n: 24,
+                r: 8,
+                p: 1,
+            },
+            Kdf::Scrypt {
+                log_n: 4,
+                r: 8,
+                p: u32::max_value(),
+            },
+            Kdf::Pbkdf2 {
+                c: u32::max_value(),
+            },
+        ] {
+            let result = Keystore::encrypt(&sk, "pw", kdf, &mut rng);
+            assert_eq!(Err(Error::InvalidKeystore), result);
+        }
+
+        // The same applies to the parameters in a keystore file, and to a malformed salt.
+        let keystore = Keystore::encrypt(&sk, "pw", TEST_KDFS[0], &mut rng).expect("encrypts");
+        let mut tampered = keystore.clone();
+        tampered.crypto.kdf.params = KdfParams::Scrypt {
+            dklen: DK_LEN,
+            n: 1 << 30,
+            r: 8,
+            p: 1,
+            salt: String::new(),
+        };
+        assert_eq!(Err(Error::InvalidKeystore), tampered.decrypt("pw"));
+        let mut tampered = keystore;
+        tampered.crypto.kdf.params = KdfParams::Scrypt {
+            dklen: DK_LEN,
+            n: 16,
+            r: 8,
+            p: 1,
+            salt: "not hex".to_string(),
+        };
+        assert_eq!(Err(Error::InvalidKeystore), tampered.decrypt("pw"));
+    }
+
+    #[test]
+    fn test_share_keystore() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng).expect("Failed to create key set");
+        let pk_set = sk_set.public_keys();
+        let share = sk_set.secret_key_share(3).expect("Failed to create share");
+        let kdf = TEST_KDFS[0];
+        let result = Keystore::encrypt_share(&share, 4, &pk_set, "pw", kdf, &mut rng);
+        assert_eq!(Err(Error::InvalidShare), result);
+        let keystore =
+            Keystore::encrypt_share(&share, 3, &pk_set, "pw", kdf, &mut rng).expect("encrypts");
+        let keystore = Keystore::from_json(&keystore.to_json()).expect("valid keystore");
+        let info = keystore.share_info().expect("share info");
+        assert_eq!(3, info.index());
+        assert_eq!(2, info.threshold());
+        assert!(info.matches(&pk_set));
+        assert_eq!(Ok(share), keystore.decrypt_share("pw", &pk_set));
+
+        // The share doesn't belong to another key set.
+        let other = SecretKeySet::random(2, &mut rng)
+            .expect("Failed to create key set")
+            .public_keys();
+        assert!(!info.matches(&other));
+        assert_eq!(
+            Err(Error::InvalidShare),
+            keystore.decrypt_share("pw", &other)
+        );
+
+        // A keystore for a single key doesn't contain a share.
+        let keystore = Keystore::encrypt(
+            &sk_set.secret_key_share(0).expect("share").0,
+            "pw",
+            kdf,
+            &mut rng,
+        )
+        .expect("encrypts");
+        assert_eq!(None, keystore.share_info());
+        assert_eq!(
+            Err(Error::InvalidKeystore),
+            keystore.decrypt_share("pw", &pk_set)
+        );
+    }
+
+    #[test]
+    fn test_eip2335_vectors() {
+        let secret = "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f";
+        let password = "\u{1d531}\u{1d522}\u{1d530}\u{1d531}\u{1d52d}\u{1d51e}\u{1d530}\u{1d530}\u{1d534}\u{1d52c}\u{1d52f}\u{1d521}\u{1f511}";
+        let json = r#"{
+            "crypto": {
+                "kdf": {
+                    "function": "pbkdf2",
+                    "params": {
+                        "dklen": 32,
+                        "c": 262144,
+                        "prf": "hmac-sha256",
+                        "salt": "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+                    },
+                    "message": ""
+                },
+                "checksum": {
+                    "function": "sha256",
+                    "params": {},
+                    "message": "8a9f5d9912ed7e75ea794bc5a89bca5f193721d30868ade6f73043c6ea6febf1"
+                },
+                "cipher": {
+                    "function": "aes-128-ctr",
+                    "params": {
+                        "iv": "264daa3f303d7259501c93d997d84fe6"
+                    },
+                    "message": "cee03fde2af33149775b7223e7845e4fb2c8ae1792e5f99fe9ecf474cc8c16ad"
+                }
+            },
+            "description": "This is a test keystore that uses PBKDF2 to secure the secret.",
+            "pubkey": "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07",
+            "path": "m/12381/60/0/0",
+            "uuid": "64625def-3331-4eea-ab6f-782f3ed16a83",
+            "version": 4
+        }"#;
+        let keystore = Keystore::from_json(json).expect("valid keystore");
+        assert_eq!("m/12381/60/0/0", keystore.path());
+        let sk = keystore.decrypt(password).expect("decrypts");
+        let mut bytes = [0u8; SK_SIZE];
+        sk.to_bytes(&mut bytes);
+        assert_eq!(secret, hex::encode(bytes));
+
+        // The same secret, secured with scrypt.
+        let mut keystore = keystore;
+        keystore.crypto.kdf.function = "scrypt".to_string();
+        keystore.crypto.kdf.params = KdfParams::Scrypt {
+            dklen: 32,
+            n: 262_144,
+            r: 8,
+            p: 1,
+            salt: "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3".to_string(),
+        };
+        keystore.crypto.checksum.message =
+            "d2217fe5f3e9a1e34581ef8a78f7c9928e436d36dacc5e846690a5581e8ea484".to_string();
+        keystore.crypto.cipher.message =
+            "06ae90d55fe0a6e9c5c3bc5b170827b2e5cce3929ed3f116c2811e6366dfe20f".to_string();
+        assert_eq!(Ok(sk), keystore.decrypt(password));
+    }
+}
@@ -2,51 +2,87 @@
 // `pairing::bls12_381` types don't implement `Hash`, so we can't derive it.
 #![cfg_attr(feature = "cargo-clippy", allow(derive_hash_xor_eq))]
 
+extern crate aes;
 #[cfg(test)]
 extern crate bincode;
+extern crate bip39;
+extern crate bls12_381;
 extern crate byteorder;
 extern crate errno;
 #[macro_use]
 extern crate failure;
+extern crate hex;
+extern crate hkdf;
+extern crate hmac;
 extern crate init_with;
 #[macro_use]
 extern crate lazy_static;
-#[macro_use]
-extern crate log;
 extern crate memsec;
 extern crate pairing;
+extern crate pbkdf2;
 extern crate rand;
 #[macro_use]
 extern crate rand_derive;
+extern crate scrypt;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+extern crate serde_json;
+extern crate sha2;
 extern crate tiny_keccak;
-
+extern crate unicode_normalization;
+
+pub mod backup;
+pub mod beacon;
+pub mod blind;
+pub mod coin;
+pub mod credential;
+pub mod derive;
+pub mod dkg;
+pub mod drand;
 pub mod error;
 mod into_fr;
+pub mod keystore;
+pub mod mnemonic;
+pub mod nidkg;
+pub mod pedersen;
 pub mod poly;
+pub mod prf;
+pub mod pvss;
+pub mod refresh;
+pub mod repair;
+pub mod reshare;
 pub mod serde_impl;
+pub mod tweak;
+pub mod vrf;
 
+use std::borrow::Borrow;
 use std::env;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::mem::size_of_val;
+use std::ops;
 use std::ptr::{copy_nonoverlapping, write_volatile};
 
+use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
 use byteorder::{BigEndian, ByteOrder};
 use errno::errno;
 use init_with::InitWith;
 use memsec::{memzero, mlock, munlock};
-use pairing::bls12_381::{Bls12, Fr, G1, G1Affine, G2, G2Affine};
-use pairing::{CurveAffine, CurveProjective, Engine, Field};
+use pairing::bls12_381::{Bls12, Fr, FrRepr, G1, G1Affine, G2, G2Affine};
+use pairing::{
+    CurveAffine, CurveProjective, EncodedPoint, Engine, Field, PrimeField, PrimeFieldRepr,
+};
 use rand::{ChaChaRng, OsRng, Rand, Rng, SeedableRng};
+use sha2::Sha256;
 use tiny_keccak::sha3_256;
 
 use error::{Error, Result};
 use into_fr::IntoFr;
 use poly::{Commitment, Poly};
 
+pub use serde_impl::SerdeSecret;
+
 lazy_static! {
     // Sets whether or not `mlock`ing is enabled. Memory locking is enabled by default; it can be
     // disabled by setting the environment variable `MLOCK_SECRETS=false`. This is useful when you
@@ -114,6 +150,15 @@ impl<'a> fmt::Debug for HexBytes<'a> {
 /// The number of words (`u32`) in a ChaCha RNG seed.
 const CHACHA_RNG_SEED_SIZE: usize = 8;
 
+/// The size of a secret key's or secret key share's byte representation.
+pub const SK_SIZE: usize = 32;
+
+/// The minimum size of the input keying material for `SecretKey::key_gen`.
+const MIN_IKM_SIZE: usize = 32;
+
+/// The key information prefix for the coefficients of a `SecretKeySet` derived from a seed.
+const SEED_COEFF_INFO: &[u8] = b"threshold_crypto-SecretKeySet-coeff-";
+
 const ERR_OS_RNG: &str = "could not initialize the OS random number generator";
 
 /// A public key.
@@ -134,6 +179,51 @@ impl fmt::Debug for PublicKey {
     }
 }
 
+impl<B: Borrow<PublicKey>> ops::AddAssign<B> for PublicKey {
+    fn add_assign(&mut self, rhs: B) {
+        self.0.add_assign(&rhs.borrow().0);
+    }
+}
+
+impl<B: Borrow<PublicKey>> ops::Add<B> for PublicKey {
+    type Output = PublicKey;
+
+    fn add(mut self, rhs: B) -> PublicKey {
+        self += rhs;
+        self
+    }
+}
+
+impl<B: Borrow<PublicKey>> ops::SubAssign<B> for PublicKey {
+    fn sub_assign(&mut self, rhs: B) {
+        self.0.sub_assign(&rhs.borrow().0);
+    }
+}
+
+impl<B: Borrow<PublicKey>> ops::Sub<B> for PublicKey {
+    type Output = PublicKey;
+
+    fn sub(mut self, rhs: B) -> PublicKey {
+        self -= rhs;
+        self
+    }
+}
+
+impl ops::MulAssign<Fr> for PublicKey {
+    fn mul_assign(&mut self, rhs: Fr) {
+        self.0.mul_assign(rhs);
+    }
+}
+
+impl ops::Mul<Fr> for PublicKey {
+    type Output = PublicKey;
+
+    fn mul(mut self, rhs: Fr) -> PublicKey {
+        self *= rhs;
+        self
+    }
+}
+
 impl PublicKey {
     /// Returns `true` if the signature matches the element of `G2`.
     pub fn verify_g2<H: Into<G2Affine>>(&self, sig: &Signature, hash: H) -> bool {
@@ -161,6 +251,15 @@ impl PublicKey {
     pub fn to_bytes(&self) -> Vec<u8> {
         self.0.into_affine().into_compressed().as_ref().to_vec()
     }
+
+    /// Returns the public key with the given byte string representation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidBytes` if the bytes don't represent a public key.
+    pub fn from_bytes<B: AsRef<[u8]>>(bytes: B) -> Result<Self> {
+        decompress(bytes.as_ref()).map(PublicKey)
+    }
 }
 
 /// A public key share.
@@ -175,6 +274,75 @@ impl fmt::Debug for PublicKeyShare {
     }
 }
 
+impl<B: Borrow<PublicKeyShare>> ops::AddAssign<B> for PublicKeyShare {
+    fn add_assign(&mut self, rhs: B) {
+        self.0 += &rhs.borrow().0;
+    }
+}
+
+impl<B: Borrow<PublicKeyShare>> ops::Add<B> for &PublicKeyShare {
+    type Output = PublicKeyShare;
+
+    fn add(self, rhs: B) -> PublicKeyShare {
+        self.clone() + rhs
+    }
+}
+
+impl<B: Borrow<PublicKeyShare>> ops::Add<B> for PublicKeyShare {
+    type Output = PublicKeyShare;
+
+    fn add(mut self, rhs: B) -> PublicKeyShare {
+        self += rhs;
+        self
+    }
+}
+
+impl<B: Borrow<PublicKeyShare>> ops::SubAssign<B> for PublicKeyShare {
+    fn sub_assign(&mut self, rhs: B) {
+        self.0 -= &rhs.borrow().0;
+    }
+}
+
+impl<B: Borrow<PublicKeyShare>> ops::Sub<B> for &PublicKeyShare {
+    type Output = PublicKeyShare;
+
+    fn sub(self, rhs: B) -> PublicKeyShare {
+        self.clone() - rhs
+    }
+}
+
+impl<B: Borrow<PublicKeyShare>> ops::Sub<B> for PublicKeyShare {
+    type Output = PublicKeyShare;
+
+    fn sub(mut self, rhs: B) -> PublicKeyShare {
+        self -= rhs;
+        self
+    }
+}
+
+impl ops::MulAssign<Fr> for PublicKeyShare {
+    fn mul_assign(&mut self, rhs: Fr) {
+        self.0 *= rhs;
+    }
+}
+
+impl ops::Mul<Fr> for &PublicKeyShare {
+    type Output = PublicKeyShare;
+
+    fn mul(self, rhs: Fr) -> PublicKeyShare {
+        self.clone() * rhs
+    }
+}
+
+impl ops::Mul<Fr> for PublicKeyShare {
+    type Output = PublicKeyShare;
+
+    fn mul(mut self, rhs: Fr) -> PublicKeyShare {
+        self *= rhs;
+        self
+    }
+}
+
 impl PublicKeyShare {
     /// Returns `true` if the signature matches the element of `G2`.
     pub fn verify_g2<H: Into<G2Affine>>(&self, sig: &SignatureShare, hash: H) -> bool {
@@ -219,13 +387,27 @@ impl Hash for Signature {
 }
 
 impl Signature {
+    /// Returns a pseudorandom bit derived from the signature.
+    #[deprecated(note = "use `Signature::coin` instead")]
     pub fn parity(&self) -> bool {
         let uncomp = self.0.into_affine().into_uncompressed();
         let bytes = uncomp.as_ref();
         let xor_bytes: u8 = bytes.iter().fold(0, |result, byte| result ^ byte);
-        let parity = 0 != xor_bytes.count_ones() % 2;
-        debug!("Signature: {:?}, output: {}", HexBytes(bytes), parity);
-        parity
+        0 != xor_bytes.count_ones() % 2
+    }
+
+    /// Returns a byte string representation of the signature.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.into_affine().into_compressed().as_ref().to_vec()
+    }
+
+    /// Returns the signature with the given byte string representation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidBytes` if the bytes don't represent a signature.
+    pub fn from_bytes<B: AsRef<[u8]>>(bytes: B) -> Result<Self> {
+        decompress(bytes.as_ref()).map(Signature)
     }
 }
 
@@ -365,6 +547,90 @@ impl ContainsSecret for SecretKey {
     }
 }
 
+/// Adds the other key's prime field element in place, so the result stays in locked memory.
+impl<B: Borrow<SecretKey>> ops::AddAssign<B> for SecretKey {
+    fn add_assign(&mut self, rhs: B) {
+        self.0.add_assign(&rhs.borrow().0);
+    }
+}
+
+/// # Panics
+///
+/// Panics if we have hit the system's locked memory limit when `mlock`ing the new instance of
+/// `SecretKey`.
+impl<B: Borrow<SecretKey>> ops::Add<B> for &SecretKey {
+    type Output = SecretKey;
+
+    fn add(self, rhs: B) -> SecretKey {
+        self.clone() + rhs
+    }
+}
+
+impl<B: Borrow<SecretKey>> ops::Add<B> for SecretKey {
+    type Output = SecretKey;
+
+    fn add(mut self, rhs: B) -> SecretKey {
+        self += rhs;
+        self
+    }
+}
+
+/// Subtracts the other key's prime field element in place, so the result stays in locked memory.
+impl<B: Borrow<SecretKey>> ops::SubAssign<B> for SecretKey {
+    fn sub_assign(&mut self, rhs: B) {
+        self.0.sub_assign(&rhs.borrow().0);
+    }
+}
+
+/// # Panics
+///
+/// Panics if we have hit the system's locked memory limit when `mlock`ing the new instance of
+/// `SecretKey`.
+impl<B: Borrow<SecretKey>> ops::Sub<B> for &SecretKey {
+    type Output = SecretKey;
+
+    fn sub(self, rhs: B) -> SecretKey {
+        self.clone() - rhs
+    }
+}
+
+impl<B: Borrow<SecretKey>> ops::Sub<B> for SecretKey {
+    type Output = SecretKey;
+
+    fn sub(mut self, rhs: B) -> SecretKey {
+        self -= rhs;
+        self
+    }
+}
+
+/// Multiplies the prime field element in place, so the result stays in locked memory.
+impl ops::MulAssign<Fr> for SecretKey {
+    fn mul_assign(&mut self, rhs: Fr) {
+        self.0.mul_assign(&rhs);
+    }
+}
+
+/// # Panics
+///
+/// Panics if we have hit the system's locked memory limit when `mlock`ing the new instance of
+/// `SecretKey`.
+impl ops::Mul<Fr> for &SecretKey {
+    type Output = SecretKey;
+
+    fn mul(self, rhs: Fr) -> SecretKey {
+        self.clone() * rhs
+    }
+}
+
+impl ops::Mul<Fr> for SecretKey {
+    type Output = SecretKey;
+
+    fn mul(mut self, rhs: Fr) -> SecretKey {
+        self *= rhs;
+        self
+    }
+}
+
 impl SecretKey {
     /// Creates a new `SecretKey` given a mutable raw pointer to a prime
     /// field element. This constructor takes a pointer to avoid any
@@ -404,6 +670,24 @@ impl SecretKey {
         SecretKey::rand(&mut rng)
     }
 
+    /// Deterministically derives a key from the input keying material and the key information,
+    /// following the `KeyGen` procedure of the IETF BLS signature draft: HKDF with SHA-256,
+    /// reduced modulo the group order and repeated with a new salt if the result is zero.
+    ///
+    /// The input keying material must be at least 32 bytes of secret randomness. The key
+    /// information can be used to derive several independent keys from it, and may be empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::SeedTooShort` if the input keying material has fewer than 32 bytes, or
+    /// an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn key_gen<I: AsRef<[u8]>, K: AsRef<[u8]>>(ikm: I, key_info: K) -> Result<Self> {
+        if ikm.as_ref().len() < MIN_IKM_SIZE {
+            return Err(Error::SeedTooShort);
+        }
+        derive::hkdf_mod_r(ikm.as_ref(), key_info.as_ref())
+    }
+
     /// Returns the matching public key.
     pub fn public_key(&self) -> PublicKey {
         PublicKey(G1Affine::one().mul(*self.0))
@@ -437,6 +721,79 @@ impl SecretKey {
         let bytes = uncomp.as_ref();
         format!("SecretKey({:?})", HexBytes(bytes))
     }
+
+    /// Writes the key's big-endian byte representation into the given buffer.
+    ///
+    /// *WARNING* the buffer then contains the secret: The caller is responsible for overwriting it
+    /// with zeros after use.
+    pub fn to_bytes(&self, bytes: &mut [u8; SK_SIZE]) {
+        let mut repr = self.0.into_repr();
+        repr.write_be(&mut bytes[..])
+            .expect("the buffer has the size of the representation");
+        unsafe {
+            memzero(&mut repr as *mut FrRepr as *mut u8, size_of_val(&repr));
+        }
+    }
+
+    /// Creates a key from its big-endian byte representation.
+    ///
+    /// *WARNING* this constructor will overwrite the given buffer with zeros, regardless of
+    /// whether it succeeds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidBytes` if the bytes don't represent a field element, or an
+    /// `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn from_bytes(bytes: &mut [u8; SK_SIZE]) -> Result<Self> {
+        let mut repr = FrRepr::default();
+        let read = repr.read_be(&bytes[..]);
+        unsafe {
+            memzero(bytes.as_mut_ptr(), bytes.len());
+        }
+        let result = match (read, Fr::from_repr(repr)) {
+            (Ok(()), Ok(mut fr)) => SecretKey::from_mut_ptr(&mut fr as *mut Fr),
+            _ => Err(Error::InvalidBytes),
+        };
+        unsafe {
+            memzero(&mut repr as *mut FrRepr as *mut u8, size_of_val(&repr));
+        }
+        result
+    }
+
+    /// Returns the key's byte representation, encrypted to the given public key.
+    pub(crate) fn encrypt_to(&self, pk: &PublicKey) -> Ciphertext {
+        let mut bytes = [0u8; SK_SIZE];
+        self.to_bytes(&mut bytes);
+        let ct = pk.encrypt(&bytes[..]);
+        unsafe {
+            memzero(bytes.as_mut_ptr(), bytes.len());
+        }
+        ct
+    }
+
+    /// Decrypts a key that was encrypted to this key's public key with `encrypt_to`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidShare` if the ciphertext is invalid or doesn't contain a key, or
+    /// an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub(crate) fn decrypt_secret_key(&self, ct: &Ciphertext) -> Result<SecretKey> {
+        let mut plaintext = self.decrypt(ct).ok_or(Error::InvalidShare)?;
+        let result = if plaintext.len() == SK_SIZE {
+            let mut bytes = [0u8; SK_SIZE];
+            bytes.copy_from_slice(&plaintext);
+            SecretKey::from_bytes(&mut bytes).map_err(|err| match err {
+                Error::InvalidBytes => Error::InvalidShare,
+                err => err,
+            })
+        } else {
+            Err(Error::InvalidShare)
+        };
+        unsafe {
+            memzero(plaintext.as_mut_ptr(), plaintext.len());
+        }
+        result
+    }
 }
 
 /// A secret key share.
@@ -451,6 +808,87 @@ impl fmt::Debug for SecretKeyShare {
     }
 }
 
+impl<B: Borrow<SecretKeyShare>> ops::AddAssign<B> for SecretKeyShare {
+    fn add_assign(&mut self, rhs: B) {
+        self.0 += &rhs.borrow().0;
+    }
+}
+
+/// # Panics
+///
+/// Panics if we have hit the system's locked memory limit when `mlock`ing the new instance of
+/// `SecretKeyShare`.
+impl<B: Borrow<SecretKeyShare>> ops::Add<B> for &SecretKeyShare {
+    type Output = SecretKeyShare;
+
+    fn add(self, rhs: B) -> SecretKeyShare {
+        self.clone() + rhs
+    }
+}
+
+impl<B: Borrow<SecretKeyShare>> ops::Add<B> for SecretKeyShare {
+    type Output = SecretKeyShare;
+
+    fn add(mut self, rhs: B) -> SecretKeyShare {
+        self += rhs;
+        self
+    }
+}
+
+impl<B: Borrow<SecretKeyShare>> ops::SubAssign<B> for SecretKeyShare {
+    fn sub_assign(&mut self, rhs: B) {
+        self.0 -= &rhs.borrow().0;
+    }
+}
+
+/// # Panics
+///
+/// Panics if we have hit the system's locked memory limit when `mlock`ing the new instance of
+/// `SecretKeyShare`.
+impl<B: Borrow<SecretKeyShare>> ops::Sub<B> for &SecretKeyShare {
+    type Output = SecretKeyShare;
+
+    fn sub(self, rhs: B) -> SecretKeyShare {
+        self.clone() - rhs
+    }
+}
+
+impl<B: Borrow<SecretKeyShare>> ops::Sub<B> for SecretKeyShare {
+    type Output = SecretKeyShare;
+
+    fn sub(mut self, rhs: B) -> SecretKeyShare {
+        self -= rhs;
+        self
+    }
+}
+
+impl ops::MulAssign<Fr> for SecretKeyShare {
+    fn mul_assign(&mut self, rhs: Fr) {
+        self.0 *= rhs;
+    }
+}
+
+/// # Panics
+///
+/// Panics if we have hit the system's locked memory limit when `mlock`ing the new instance of
+/// `SecretKeyShare`.
+impl ops::Mul<Fr> for &SecretKeyShare {
+    type Output = SecretKeyShare;
+
+    fn mul(self, rhs: Fr) -> SecretKeyShare {
+        self.clone() * rhs
+    }
+}
+
+impl ops::Mul<Fr> for SecretKeyShare {
+    type Output = SecretKeyShare;
+
+    fn mul(mut self, rhs: Fr) -> SecretKeyShare {
+        self *= rhs;
+        self
+    }
+}
+
 impl SecretKeyShare {
     /// Creates a secret key share from an existing value. This constructor
     /// takes a pointer to avoid any unnecessary stack copying/moving of
@@ -505,6 +943,27 @@ impl SecretKeyShare {
         let bytes = uncomp.as_ref();
         format!("SecretKeyShare({:?})", HexBytes(bytes))
     }
+
+    /// Writes the share's big-endian byte representation into the given buffer.
+    ///
+    /// *WARNING* the buffer then contains the secret: The caller is responsible for overwriting it
+    /// with zeros after use.
+    pub fn to_bytes(&self, bytes: &mut [u8; SK_SIZE]) {
+        self.0.to_bytes(bytes)
+    }
+
+    /// Creates a share from its big-endian byte representation.
+    ///
+    /// *WARNING* this constructor will overwrite the given buffer with zeros, regardless of
+    /// whether it succeeds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidBytes` if the bytes don't represent a field element, or an
+    /// `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn from_bytes(bytes: &mut [u8; SK_SIZE]) -> Result<Self> {
+        SecretKey::from_bytes(bytes).map(SecretKeyShare)
+    }
 }
 
 /// An encrypted message.
@@ -564,6 +1023,75 @@ impl From<Commitment> for PublicKeySet {
     }
 }
 
+impl<B: Borrow<PublicKeySet>> ops::AddAssign<B> for PublicKeySet {
+    fn add_assign(&mut self, rhs: B) {
+        self.commit += &rhs.borrow().commit;
+    }
+}
+
+impl<B: Borrow<PublicKeySet>> ops::Add<B> for &PublicKeySet {
+    type Output = PublicKeySet;
+
+    fn add(self, rhs: B) -> PublicKeySet {
+        self.clone() + rhs
+    }
+}
+
+impl<B: Borrow<PublicKeySet>> ops::Add<B> for PublicKeySet {
+    type Output = PublicKeySet;
+
+    fn add(mut self, rhs: B) -> PublicKeySet {
+        self += rhs;
+        self
+    }
+}
+
+impl<B: Borrow<PublicKeySet>> ops::SubAssign<B> for PublicKeySet {
+    fn sub_assign(&mut self, rhs: B) {
+        self.commit -= &rhs.borrow().commit;
+    }
+}
+
+impl<B: Borrow<PublicKeySet>> ops::Sub<B> for &PublicKeySet {
+    type Output = PublicKeySet;
+
+    fn sub(self, rhs: B) -> PublicKeySet {
+        self.clone() - rhs
+    }
+}
+
+impl<B: Borrow<PublicKeySet>> ops::Sub<B> for PublicKeySet {
+    type Output = PublicKeySet;
+
+    fn sub(mut self, rhs: B) -> PublicKeySet {
+        self -= rhs;
+        self
+    }
+}
+
+impl ops::MulAssign<Fr> for PublicKeySet {
+    fn mul_assign(&mut self, rhs: Fr) {
+        self.commit *= rhs;
+    }
+}
+
+impl ops::Mul<Fr> for &PublicKeySet {
+    type Output = PublicKeySet;
+
+    fn mul(self, rhs: Fr) -> PublicKeySet {
+        self.clone() * rhs
+    }
+}
+
+impl ops::Mul<Fr> for PublicKeySet {
+    type Output = PublicKeySet;
+
+    fn mul(mut self, rhs: Fr) -> PublicKeySet {
+        self *= rhs;
+        self
+    }
+}
+
 impl PublicKeySet {
     /// Returns the threshold `t`: any set of `t + 1` signature shares can be combined into a full
     /// signature.
@@ -571,6 +1099,41 @@ impl PublicKeySet {
         self.commit.degree()
     }
 
+    /// Returns the key set with the given threshold that contains the given public key shares.
+    ///
+    /// The first `threshold + 1` shares determine the key set. Any further shares are checked
+    /// against it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::NotEnoughShares` if there are fewer than `threshold + 1` shares, an
+    /// `Error::DuplicateEntry` if an index appears twice, or an `Error::InvalidShare` if the extra
+    /// shares don't belong to the same key set.
+    pub fn from_shares<'a, T, I>(threshold: usize, shares: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = (T, &'a PublicKeyShare)>,
+        T: IntoFr,
+    {
+        let samples: Vec<(Fr, G1)> = shares
+            .into_iter()
+            .map(|(i, share)| (into_fr_plus_1(i), (share.0).0))
+            .collect();
+        if samples.len() <= threshold {
+            return Err(Error::NotEnoughShares);
+        }
+        let (samples, extra) = samples.split_at(threshold + 1);
+        let commit = Commitment::interpolate(samples)?;
+        for (i, (x, value)) in extra.iter().enumerate() {
+            if samples.iter().chain(&extra[..i]).any(|(x0, _)| x0 == x) {
+                return Err(Error::DuplicateEntry);
+            }
+            if commit.evaluate(*x) != *value {
+                return Err(Error::InvalidShare);
+            }
+        }
+        Ok(PublicKeySet { commit })
+    }
+
     /// Returns the public key.
     pub fn public_key(&self) -> PublicKey {
         PublicKey(self.commit.coeff[0])
@@ -602,6 +1165,49 @@ impl PublicKeySet {
         let g = interpolate(self.commit.degree() + 1, samples)?;
         Ok(xor_vec(&hash_bytes(g, ct.1.len()), &ct.1))
     }
+
+    /// Combines `threshold + 1` secret key shares into the master secret key.
+    ///
+    /// *WARNING* this reveals the master key to whoever calls it, and removes the protection of
+    /// the threshold scheme. It is only meant for emergency recovery procedures, e.g. to migrate
+    /// the key elsewhere.
+    ///
+    /// All intermediate values are kept in `mlock`ed memory and zeroed afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::NotEnoughShares` if there are fewer than `threshold + 1` shares, an
+    /// `Error::DuplicateEntry` if an index appears twice, an `Error::InvalidShare` if the result
+    /// doesn't match the public key, or an `Error::MlockFailed` if we have reached the systems's
+    /// locked memory limit.
+    pub fn reconstruct_secret_key<'a, T, I>(&self, shares: I) -> Result<SecretKey>
+    where
+        I: IntoIterator<Item = (T, &'a SecretKeyShare)>,
+        T: IntoFr,
+    {
+        let shares: Vec<_> = shares
+            .into_iter()
+            .take(self.threshold() + 1)
+            .map(|(i, share)| (into_fr_plus_1(i), share))
+            .collect();
+        if shares.len() <= self.threshold() {
+            return Err(Error::NotEnoughShares);
+        }
+        let xs: Vec<Fr> = shares.iter().map(|(x, _)| *x).collect();
+        let coeffs = lagrange_coefficients(&xs)?;
+        let mut fr = Fr::zero();
+        let mut sk = SecretKey::from_mut_ptr(&mut fr as *mut Fr)?;
+        let mut term = SecretKey::from_mut_ptr(&mut fr as *mut Fr)?;
+        for ((_, share), l0) in shares.iter().zip(coeffs) {
+            *term.0 = *(share.0).0;
+            term.0.mul_assign(&l0);
+            sk.0.add_assign(&term.0);
+        }
+        if sk.public_key() != self.public_key() {
+            return Err(Error::InvalidShare);
+        }
+        Ok(sk)
+    }
 }
 
 /// A secret key and an associated set of secret key shares.
@@ -617,6 +1223,112 @@ impl From<Poly> for SecretKeySet {
     }
 }
 
+/// # Panics
+///
+/// Panics if we hit the system's locked memory limit or if we fail to unlock memory that has been
+/// truncated from the polynomial.
+impl<B: Borrow<SecretKeySet>> ops::AddAssign<B> for SecretKeySet {
+    fn add_assign(&mut self, rhs: B) {
+        self.poly += &rhs.borrow().poly;
+        self.keep_constant_term();
+    }
+}
+
+/// # Panics
+///
+/// Panics if we hit the system's locked memory limit or if we fail to unlock memory that has been
+/// truncated from the polynomial.
+impl<B: Borrow<SecretKeySet>> ops::Add<B> for &SecretKeySet {
+    type Output = SecretKeySet;
+
+    fn add(self, rhs: B) -> SecretKeySet {
+        let mut result = SecretKeySet::from(&self.poly + &rhs.borrow().poly);
+        result.keep_constant_term();
+        result
+    }
+}
+
+impl<B: Borrow<SecretKeySet>> ops::Add<B> for SecretKeySet {
+    type Output = SecretKeySet;
+
+    fn add(mut self, rhs: B) -> SecretKeySet {
+        self += rhs;
+        self
+    }
+}
+
+/// # Panics
+///
+/// Panics if we hit the system's locked memory limit or if we fail to unlock memory that has been
+/// truncated from the polynomial.
+impl<B: Borrow<SecretKeySet>> ops::SubAssign<B> for SecretKeySet {
+    fn sub_assign(&mut self, rhs: B) {
+        self.poly -= &rhs.borrow().poly;
+        self.keep_constant_term();
+    }
+}
+
+/// # Panics
+///
+/// Panics if we hit the system's locked memory limit or if we fail to unlock memory that has been
+/// truncated from the polynomial.
+impl<B: Borrow<SecretKeySet>> ops::Sub<B> for &SecretKeySet {
+    type Output = SecretKeySet;
+
+    fn sub(self, rhs: B) -> SecretKeySet {
+        let mut result = SecretKeySet::from(&self.poly - &rhs.borrow().poly);
+        result.keep_constant_term();
+        result
+    }
+}
+
+impl<B: Borrow<SecretKeySet>> ops::Sub<B> for SecretKeySet {
+    type Output = SecretKeySet;
+
+    fn sub(mut self, rhs: B) -> SecretKeySet {
+        self -= rhs;
+        self
+    }
+}
+
+/// # Panics
+///
+/// Panics if we hit the system's locked memory limit, or if we fail to unlock the cleared
+/// polynomial when multiplying by zero.
+impl ops::Mul<Fr> for &SecretKeySet {
+    type Output = SecretKeySet;
+
+    fn mul(self, rhs: Fr) -> SecretKeySet {
+        let mut result = SecretKeySet::from(self.poly.clone() * rhs);
+        result.keep_constant_term();
+        result
+    }
+}
+
+/// # Panics
+///
+/// Panics if we hit the system's locked memory limit, or if we fail to unlock the cleared
+/// polynomial when multiplying by zero.
+impl ops::MulAssign<Fr> for SecretKeySet {
+    fn mul_assign(&mut self, rhs: Fr) {
+        self.poly *= rhs;
+        self.keep_constant_term();
+    }
+}
+
+/// # Panics
+///
+/// Panics if we hit the system's locked memory limit, or if we fail to unlock the cleared
+/// polynomial when multiplying by zero.
+impl ops::Mul<Fr> for SecretKeySet {
+    type Output = SecretKeySet;
+
+    fn mul(mut self, rhs: Fr) -> SecretKeySet {
+        self *= rhs;
+        self
+    }
+}
+
 impl SecretKeySet {
     /// Creates a set of secret key shares, where any `threshold + 1` of them can collaboratively
     /// sign and decrypt.
@@ -625,6 +1337,45 @@ impl SecretKeySet {
         Ok(SecretKeySet { poly })
     }
 
+    /// Creates a set of secret key shares of the given secret key, where any `threshold + 1` of
+    /// them can collaboratively sign and decrypt. The resulting `PublicKeySet`'s `public_key()` is
+    /// the key's public key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn from_secret_key<R: Rng>(sk: &SecretKey, threshold: usize, rng: &mut R) -> Result<Self> {
+        let mut poly = Poly::random(threshold, rng)?;
+        poly.coeff[0] = *sk.0;
+        Ok(SecretKeySet { poly })
+    }
+
+    /// Deterministically derives a set of secret key shares from the seed, where any
+    /// `threshold + 1` of them can collaboratively sign and decrypt. The seed must be at least 32
+    /// bytes of secret randomness.
+    ///
+    /// The master key is `SecretKey::key_gen(seed, "")`, and the other coefficients are derived
+    /// with `key_gen`, too, with the threshold and their index as key information. The same seed
+    /// and threshold always produce the same key set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::SeedTooShort` if the seed has fewer than 32 bytes, or an
+    /// `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn from_seed<S: AsRef<[u8]>>(threshold: usize, seed: S) -> Result<Self> {
+        // The coefficients are written into the `mlock`ed polynomial, which zeroes them when it is
+        // dropped if a later key derivation fails.
+        let mut poly = Poly::new(vec![Fr::zero(); threshold + 1])?;
+        poly.coeff[0] = *SecretKey::key_gen(seed.as_ref(), b"")?.0;
+        for i in 1..=threshold {
+            let mut key_info = SEED_COEFF_INFO.to_vec();
+            key_info.extend_from_slice(&(threshold as u64).to_be_bytes());
+            key_info.extend_from_slice(&(i as u64).to_be_bytes());
+            poly.coeff[i] = *SecretKey::key_gen(seed.as_ref(), &key_info)?.0;
+        }
+        Ok(SecretKeySet { poly })
+    }
+
     /// Returns the threshold `t`: any set of `t + 1` signature shares can be combined into a full
     /// signature.
     pub fn threshold(&self) -> usize {
@@ -650,16 +1401,56 @@ impl SecretKeySet {
         let mut fr = self.poly.evaluate(0);
         SecretKey::from_mut_ptr(&mut fr as *mut Fr)
     }
+
+    /// Replaces the polynomial with the constant zero if arithmetic removed all its coefficients,
+    /// so that the key set still has a threshold and a public key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if we hit the system's locked memory limit.
+    fn keep_constant_term(&mut self) {
+        if self.poly.coeff.is_empty() {
+            self.poly = match Poly::constant(Fr::zero()) {
+                Ok(poly) => poly,
+                Err(e) => panic!("Failed to create a zero `Poly`: {}", e),
+            };
+        }
+    }
 }
 
-/// Returns a hash of the given message in `G2`.
-fn hash_g2<M: AsRef<[u8]>>(msg: M) -> G2 {
+/// Returns a random number generator, seeded with the hash of the given message.
+fn hash_rng<M: AsRef<[u8]>>(msg: M) -> ChaChaRng {
     let digest = sha3_256(msg.as_ref());
     let seed = <[u32; CHACHA_RNG_SEED_SIZE]>::init_with_indices(|i| {
         BigEndian::read_u32(&digest.as_ref()[(4 * i)..(4 * i + 4)])
     });
-    let mut rng = ChaChaRng::from_seed(&seed);
-    rng.gen()
+    ChaChaRng::from_seed(&seed)
+}
+
+/// Returns a hash of the given message in `G2`.
+fn hash_g2<M: AsRef<[u8]>>(msg: M) -> G2 {
+    hash_rng(msg).gen()
+}
+
+/// Returns a hash of the given message in `G2`, as specified in the IETF hash-to-curve draft with
+/// the given domain separation tag. Unlike `hash_g2`, this can't be computed by signing a message.
+fn hash_to_g2<M: AsRef<[u8]>>(msg: M, dst: &[u8]) -> G2 {
+    let point = <bls12_381::G2Projective as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve(
+        msg.as_ref(),
+        dst,
+    );
+    let compressed = bls12_381::G2Affine::from(point).to_compressed();
+    decompress(&compressed[..]).expect("both libraries use the same encoding")
+}
+
+/// Returns a hash of the given message in `G1`.
+fn hash_g1<M: AsRef<[u8]>>(msg: M) -> G1 {
+    hash_rng(msg).gen()
+}
+
+/// Returns a hash of the given message in `Fr`.
+fn hash_fr<M: AsRef<[u8]>>(msg: M) -> Fr {
+    hash_rng(msg).gen()
 }
 
 /// Returns a hash of the group element and message, in the second group.
@@ -677,14 +1468,21 @@ fn hash_g1_g2<M: AsRef<[u8]>>(g1: G1, msg: M) -> G2 {
 
 /// Returns a hash of the group element with the specified length in bytes.
 fn hash_bytes(g1: G1, len: usize) -> Vec<u8> {
-    let digest = sha3_256(g1.into_affine().into_compressed().as_ref());
-    let seed = <[u32; CHACHA_RNG_SEED_SIZE]>::init_with_indices(|i| {
-        BigEndian::read_u32(&digest.as_ref()[(4 * i)..(4 * i + 4)])
-    });
-    let mut rng = ChaChaRng::from_seed(&seed);
+    let mut rng = hash_rng(g1.into_affine().into_compressed());
     rng.gen_iter().take(len).collect()
 }
 
+/// Returns the group element with the given compressed representation.
+fn decompress<C: CurveProjective>(bytes: &[u8]) -> Result<C> {
+    let mut compressed = <C::Affine as CurveAffine>::Compressed::empty();
+    if bytes.len() != compressed.as_ref().len() {
+        return Err(Error::InvalidBytes);
+    }
+    compressed.as_mut().copy_from_slice(bytes);
+    let affine = compressed.into_affine().map_err(|_| Error::InvalidBytes)?;
+    Ok(affine.into_projective())
+}
+
 /// Returns the bitwise xor.
 fn xor_vec(x: &[u8], y: &[u8]) -> Vec<u8> {
     x.iter().zip(y).map(|(a, b)| a ^ b).collect()
@@ -705,23 +1503,30 @@ where
     if samples.len() < t {
         return Err(Error::NotEnoughShares);
     }
+    let xs: Vec<Fr> = samples.iter().take(t).map(|(x, _)| *x).collect();
     let mut result = C::zero();
-    let mut indexes = Vec::new();
-    for (x, sample) in samples.iter().take(t) {
-        if indexes.contains(x) {
+    for ((_, sample), l0) in samples.iter().zip(lagrange_coefficients(&xs)?) {
+        result.add_assign(&sample.into_affine().mul(l0));
+    }
+    Ok(result)
+}
+
+/// Returns the values at `0` of the Lagrange polynomials for the given points: The `i`-th one is
+/// `1` at `xs[i]` and `0` at all the other points.
+fn lagrange_coefficients(xs: &[Fr]) -> Result<Vec<Fr>> {
+    let mut result = Vec::with_capacity(xs.len());
+    for (i, x) in xs.iter().enumerate() {
+        if xs[..i].contains(x) {
             return Err(Error::DuplicateEntry);
         }
-        indexes.push(x.clone());
-        // Compute the value at 0 of the Lagrange polynomial that is `0` at the other data
-        // points but `1` at `x`.
-        let mut l0 = C::Scalar::one();
-        for (x0, _) in samples.iter().take(t).filter(|(x0, _)| x0 != x) {
+        let mut l0 = Fr::one();
+        for x0 in xs.iter().filter(|x0| *x0 != x) {
             let mut denom = *x0;
             denom.sub_assign(x);
             l0.mul_assign(x0);
             l0.mul_assign(&denom.inverse().expect("indices are different"));
         }
-        result.add_assign(&sample.into_affine().mul(l0));
+        result.push(l0);
     }
     Ok(result)
 }
@@ -822,6 +1627,238 @@ mod tests {
         assert_eq!(sig, sig2);
     }
 
+    #[test]
+    fn test_reconstruct_secret_key() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng).expect("Failed to create `SecretKeySet`");
+        let pk_set = sk_set.public_keys();
+        let shares: BTreeMap<_, _> = [1, 4, 6, 9]
+            .iter()
+            .map(|&i| {
+                let share = sk_set
+                    .secret_key_share(i)
+                    .expect("Failed to create `SecretKeyShare`");
+                (i, share)
+            })
+            .collect();
+
+        // Any three shares reconstruct the master key.
+        let sk = pk_set
+            .reconstruct_secret_key(&shares)
+            .expect("Failed to reconstruct");
+        assert_eq!(sk_set.secret_key(), Ok(sk));
+        let sk = pk_set
+            .reconstruct_secret_key(shares.iter().skip(1))
+            .expect("Failed to reconstruct");
+        assert_eq!(pk_set.public_key(), sk.public_key());
+
+        // Two shares are not enough, and shares under the wrong index don't match the public key.
+        let result = pk_set.reconstruct_secret_key(shares.iter().take(2));
+        assert_eq!(Err(Error::NotEnoughShares), result);
+        let result = pk_set.reconstruct_secret_key(shares.values().enumerate());
+        assert_eq!(Err(Error::InvalidShare), result);
+        let duplicate = vec![(1, &shares[&1]), (1, &shares[&4]), (6, &shares[&6])];
+        let result = pk_set.reconstruct_secret_key(duplicate);
+        assert_eq!(Err(Error::DuplicateEntry), result);
+    }
+
+    #[test]
+    fn test_public_key_set_from_shares() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng).expect("Failed to create `SecretKeySet`");
+        let pk_set = sk_set.public_keys();
+        let shares: BTreeMap<_, _> = [0, 3, 5, 6]
+            .iter()
+            .map(|&i| (i, pk_set.public_key_share(i)))
+            .collect();
+
+        // Three shares determine the key set, and the fourth one is checked against it.
+        let pk_set2 = PublicKeySet::from_shares(2, &shares).expect("Failed to interpolate");
+        assert_eq!(pk_set, pk_set2);
+        assert_eq!(pk_set.public_key_share(9), pk_set2.public_key_share(9));
+        let pk_set2 = PublicKeySet::from_shares(2, shares.iter().skip(1)).expect("interpolates");
+        assert_eq!(pk_set, pk_set2);
+
+        // Too few shares, duplicate indices and shares from another key set are detected.
+        let result = PublicKeySet::from_shares(2, shares.iter().take(2));
+        assert_eq!(Err(Error::NotEnoughShares), result);
+        let duplicate = shares.iter().chain(Some((&3, &shares[&3])));
+        let result = PublicKeySet::from_shares(2, duplicate);
+        assert_eq!(Err(Error::DuplicateEntry), result);
+        let result = PublicKeySet::from_shares(1, &shares);
+        assert_eq!(Err(Error::InvalidShare), result);
+        let other = SecretKeySet::random(2, &mut rng)
+            .expect("Failed to create `SecretKeySet`")
+            .public_keys()
+            .public_key_share(7);
+        let mut wrong = shares.clone();
+        wrong.insert(7, other);
+        let result = PublicKeySet::from_shares(2, &wrong);
+        assert_eq!(Err(Error::InvalidShare), result);
+    }
+
+    #[test]
+    fn test_secret_key_set_from_secret_key() {
+        let mut rng = rand::thread_rng();
+        let sk: SecretKey = random();
+        let sk_set = SecretKeySet::from_secret_key(&sk, 2, &mut rng)
+            .expect("Failed to create `SecretKeySet`");
+        assert_eq!(2, sk_set.threshold());
+        let pk_set = sk_set.public_keys();
+        assert_eq!(sk.public_key(), pk_set.public_key());
+
+        // The shares sign for the original key.
+        let msg = "Now under threshold control";
+        let sigs: BTreeMap<_, _> = [0, 1, 2]
+            .iter()
+            .map(|&i| {
+                let share = sk_set
+                    .secret_key_share(i)
+                    .expect("Failed to create `SecretKeyShare`");
+                (i, share.sign(msg))
+            })
+            .collect();
+        let sig = pk_set.combine_signatures(&sigs).expect("signatures match");
+        assert_eq!(sk.sign(msg), sig);
+
+        // The polynomial is random apart from the constant term.
+        let sk_set2 = SecretKeySet::from_secret_key(&sk, 2, &mut rng)
+            .expect("Failed to create `SecretKeySet`");
+        assert_eq!(pk_set.public_key(), sk_set2.public_keys().public_key());
+        assert_ne!(pk_set, sk_set2.public_keys());
+    }
+
+    #[test]
+    fn test_key_gen() {
+        let ikm = [42u8; 32];
+        let sk = SecretKey::key_gen(ikm, b"").expect("Failed to generate key");
+        assert_eq!(SecretKey::key_gen(ikm, b""), Ok(sk.clone()));
+        assert_ne!(SecretKey::key_gen(ikm, b"other"), Ok(sk.clone()));
+        assert_ne!(SecretKey::key_gen([43u8; 32], b""), Ok(sk));
+        assert_eq!(
+            Err(Error::SeedTooShort),
+            SecretKey::key_gen([42u8; 31], b"")
+        );
+
+        // The same seed always produces the same key set, with the master key from `key_gen`.
+        let sk_set = SecretKeySet::from_seed(2, ikm).expect("Failed to create `SecretKeySet`");
+        assert_eq!(2, sk_set.threshold());
+        assert_eq!(SecretKey::key_gen(ikm, b""), sk_set.secret_key());
+        let sk_set2 = SecretKeySet::from_seed(2, ikm).expect("Failed to create `SecretKeySet`");
+        assert_eq!(sk_set.public_keys(), sk_set2.public_keys());
+        assert_eq!(sk_set.secret_key_share(5), sk_set2.secret_key_share(5));
+
+        // A different threshold yields unrelated shares of the same master key.
+        let sk_set3 = SecretKeySet::from_seed(3, ikm).expect("Failed to create `SecretKeySet`");
+        assert_eq!(sk_set.secret_key(), sk_set3.secret_key());
+        assert_ne!(sk_set.secret_key_share(0), sk_set3.secret_key_share(0));
+    }
+
+    #[test]
+    fn test_key_arithmetic() {
+        let mut rng = rand::thread_rng();
+        let sk_set_a = SecretKeySet::random(2, &mut rng).expect("Failed to create `SecretKeySet`");
+        let sk_set_b = SecretKeySet::random(1, &mut rng).expect("Failed to create `SecretKeySet`");
+        let pk_set_a = sk_set_a.public_keys();
+        let pk_set_b = sk_set_b.public_keys();
+        let c: Fr = rng.gen();
+
+        // Operations on secret key sets match the ones on public key sets.
+        let sum = &sk_set_a + &sk_set_b;
+        let diff = &sk_set_a - &sk_set_b;
+        let prod = &sk_set_a * c;
+        assert_eq!(2, sum.threshold());
+        assert_eq!(&pk_set_a + &pk_set_b, sum.public_keys());
+        assert_eq!(&pk_set_a - &pk_set_b, diff.public_keys());
+        assert_eq!(&pk_set_a * c, prod.public_keys());
+        assert_eq!(
+            pk_set_a.public_key() + pk_set_b.public_key(),
+            sum.public_keys().public_key()
+        );
+
+        // They also commute with taking shares.
+        for i in 0..5 {
+            let share_a = sk_set_a
+                .secret_key_share(i)
+                .expect("Failed to create share");
+            let share_b = sk_set_b
+                .secret_key_share(i)
+                .expect("Failed to create share");
+            assert_eq!(sum.secret_key_share(i), Ok(&share_a + &share_b));
+            assert_eq!(diff.secret_key_share(i), Ok(&share_a - &share_b));
+            assert_eq!(prod.secret_key_share(i), Ok(&share_a * c));
+            let pk_share_a = pk_set_a.public_key_share(i);
+            let pk_share_b = pk_set_b.public_key_share(i);
+            assert_eq!(
+                sum.public_keys().public_key_share(i),
+                &pk_share_a + &pk_share_b
+            );
+            assert_eq!(
+                diff.public_keys().public_key_share(i),
+                &pk_share_a - &pk_share_b
+            );
+            assert_eq!(prod.public_keys().public_key_share(i), pk_share_a * c);
+        }
+
+        // The same holds for secret and public keys.
+        let sk_a = sk_set_a.secret_key().expect("Failed to create key");
+        let sk_b = sk_set_b.secret_key().expect("Failed to create key");
+        let pk = (sk_a.public_key() - sk_b.public_key()) * c;
+        let sk = (&sk_a - &sk_b) * c;
+        assert_eq!(pk, sk.public_key());
+        let mut sk_sum = sk_a.clone();
+        sk_sum += &sk_b;
+        assert_eq!(sk_a.public_key() + sk_b.public_key(), sk_sum.public_key());
+
+        // The combined shares sign for the combined key.
+        let msg = "Signed by the sum of two key sets";
+        let sigs: BTreeMap<_, _> = [0, 3, 4]
+            .iter()
+            .map(|&i| {
+                let share = sum.secret_key_share(i).expect("Failed to create share");
+                (i, share.sign(msg))
+            })
+            .collect();
+        let sig = sum
+            .public_keys()
+            .combine_signatures(&sigs)
+            .expect("signatures match");
+        assert_eq!(sk_sum.sign(msg), sig);
+    }
+
+    #[test]
+    fn test_key_arithmetic_zero() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng).expect("Failed to create `SecretKeySet`");
+        let pk_set = sk_set.public_keys();
+        let zero_pk = PublicKey(G1::zero());
+
+        // Scaling by zero and subtracting a key set from itself leave a zero key set of
+        // threshold `0`.
+        let scaled = &pk_set * Fr::zero();
+        assert_eq!(0, scaled.threshold());
+        assert_eq!(zero_pk, scaled.public_key());
+        let mut scaled = pk_set.clone();
+        scaled *= Fr::zero();
+        assert_eq!(zero_pk, scaled.public_key());
+        let diff = &pk_set - &pk_set;
+        assert_eq!(0, diff.threshold());
+        assert_eq!(zero_pk, diff.public_key());
+        assert_eq!(zero_pk, diff.public_key_share(3).0);
+
+        let mut scaled = SecretKeySet::from(sk_set.poly.clone());
+        scaled *= Fr::zero();
+        let mut diff_assign = SecretKeySet::from(sk_set.poly.clone());
+        diff_assign -= &sk_set;
+        let zeros = vec![&sk_set - &sk_set, &sk_set * Fr::zero(), scaled, diff_assign];
+        for zero in zeros {
+            assert_eq!(0, zero.threshold());
+            assert_eq!(diff, zero.public_keys());
+            let share = zero.secret_key_share(3).expect("Failed to create share");
+            assert_eq!(zero_pk, share.public_key_share().0);
+        }
+    }
+
     #[test]
     fn test_simple_enc() {
         let sk_bob: SecretKey = random();
@@ -924,6 +1961,26 @@ mod tests {
         assert_eq!(20, hash(g0, 20).len());
     }
 
+    #[test]
+    fn test_secret_key_bytes() {
+        let sk: SecretKey = random();
+        let mut bytes = [0u8; SK_SIZE];
+        sk.to_bytes(&mut bytes);
+        let sk2 = SecretKey::from_bytes(&mut bytes).expect("valid secret key bytes");
+        assert_eq!(sk, sk2);
+        assert_eq!([0u8; SK_SIZE], bytes);
+
+        let share: SecretKeyShare = random();
+        share.to_bytes(&mut bytes);
+        let share2 = SecretKeyShare::from_bytes(&mut bytes).expect("valid share bytes");
+        assert_eq!(share, share2);
+
+        // The modulus and larger numbers are not valid field elements.
+        let mut bytes = [0xff; SK_SIZE];
+        assert_eq!(Err(Error::InvalidBytes), SecretKey::from_bytes(&mut bytes));
+        assert_eq!([0u8; SK_SIZE], bytes);
+    }
+
     #[test]
     fn test_serde() {
         use bincode;
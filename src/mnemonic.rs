@@ -0,0 +1,205 @@
+//! Mnemonic phrases, following
+//! [BIP-39](https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki), to back up keys on
+//! paper.
+//!
+//! A mnemonic encodes between 128 and 256 bits of entropy as 12 to 24 English words, the last of
+//! which contains a checksum. Together with an optional passphrase, it is stretched into a 64-byte
+//! seed, from which the key is derived with the `KeyGen` step of EIP-2333. The seed only exists
+//! temporarily, and is zeroed after use.
+
+use std::fmt;
+use std::ops::Deref;
+
+use bip39;
+use memsec::memzero;
+use rand::Rng;
+
+use super::error::{Error, Result};
+use super::{SecretKey, SecretKeyShare};
+
+/// The maximum number of bytes of entropy in a mnemonic.
+const MAX_ENTROPY_LEN: usize = 32;
+
+/// A mnemonic phrase. Its memory is zeroed when it is dropped.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Mnemonic(bip39::Mnemonic);
+
+impl fmt::Debug for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Mnemonic(...)")
+    }
+}
+
+impl Mnemonic {
+    /// Creates a random mnemonic with the given number of words, which must be 12, 15, 18, 21 or
+    /// 24.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidMnemonic` if the number of words is not supported.
+    pub fn random<R: Rng>(word_count: usize, rng: &mut R) -> Result<Self> {
+        match word_count {
+            12 | 15 | 18 | 21 | 24 => (),
+            _ => return Err(Error::InvalidMnemonic),
+        }
+        let mut entropy = [0u8; MAX_ENTROPY_LEN];
+        rng.fill_bytes(&mut entropy);
+        let result = bip39::Mnemonic::from_entropy(&entropy[..(word_count / 3 * 4)]);
+        unsafe {
+            memzero(entropy.as_mut_ptr(), entropy.len());
+        }
+        result.map(Mnemonic).map_err(|_| Error::InvalidMnemonic)
+    }
+
+    /// Parses a mnemonic phrase, and validates its checksum.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidMnemonic` if the phrase has an unsupported number of words,
+    /// contains unknown words, or the checksum doesn't match.
+    pub fn parse(phrase: &str) -> Result<Self> {
+        bip39::Mnemonic::parse(phrase)
+            .map(Mnemonic)
+            .map_err(|_| Error::InvalidMnemonic)
+    }
+
+    /// Returns the number of words.
+    pub fn word_count(&self) -> usize {
+        self.0.word_count()
+    }
+
+    /// Returns the phrase, with the words separated by single spaces. Its memory is zeroed when
+    /// it is dropped.
+    ///
+    /// *WARNING* Anyone who learns the phrase can restore the key: Copying it out of the returned
+    /// `Phrase` creates copies that are not zeroed.
+    pub fn phrase(&self) -> Phrase {
+        let len = self.0.words().map(|word| word.len() + 1).sum::<usize>() - 1;
+        // The capacity is exact, so the string is never reallocated and leaves no copies behind.
+        let mut phrase = String::with_capacity(len);
+        for (i, word) in self.0.words().enumerate() {
+            if i > 0 {
+                phrase.push(' ');
+            }
+            phrase.push_str(word);
+        }
+        Phrase(phrase)
+    }
+
+    /// Calls `f` with the seed for the given passphrase, and zeroes the seed afterwards.
+    fn with_seed<T, F>(&self, passphrase: &str, f: F) -> T
+    where
+        F: FnOnce(&[u8]) -> T,
+    {
+        let mut seed = self.0.to_seed(passphrase);
+        let result = f(&seed);
+        unsafe {
+            memzero(seed.as_mut_ptr(), seed.len());
+        }
+        result
+    }
+}
+
+/// A mnemonic phrase as a string. Its memory is zeroed when it is dropped.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Phrase(String);
+
+impl Deref for Phrase {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Phrase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Phrase(...)")
+    }
+}
+
+impl Drop for Phrase {
+    fn drop(&mut self) {
+        unsafe {
+            let bytes = self.0.as_bytes_mut();
+            memzero(bytes.as_mut_ptr(), bytes.len());
+        }
+    }
+}
+
+impl SecretKey {
+    /// Restores the key from the mnemonic and passphrase. This is the master key of EIP-2333 for
+    /// the mnemonic's seed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn from_mnemonic(mnemonic: &Mnemonic, passphrase: &str) -> Result<Self> {
+        mnemonic.with_seed(passphrase, |seed| SecretKey::derive_master(seed))
+    }
+}
+
+impl SecretKeyShare {
+    /// Restores the share from the mnemonic and passphrase, in the same way as
+    /// `SecretKey::from_mnemonic`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn from_mnemonic(mnemonic: &Mnemonic, passphrase: &str) -> Result<Self> {
+        SecretKey::from_mnemonic(mnemonic, passphrase).map(SecretKeyShare)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex;
+    use rand;
+
+    use super::*;
+
+    #[test]
+    fn test_bip39_vector() {
+        // The first test vector from BIP-39, whose seed is also the first test case of EIP-2333.
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                      abandon abandon about";
+        let seed = "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d1\
+                    8264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04";
+        let mnemonic = Mnemonic::parse(phrase).expect("Failed to parse mnemonic");
+        assert_eq!(12, mnemonic.word_count());
+        assert_eq!(phrase, &*mnemonic.phrase());
+        assert_eq!("Phrase(...)", format!("{:?}", mnemonic.phrase()));
+        let sk = SecretKey::from_mnemonic(&mnemonic, "TREZOR").expect("Failed to restore key");
+        let seed = hex::decode(seed).expect("valid hex");
+        assert_eq!(SecretKey::derive_master(seed), Ok(sk.clone()));
+        let other = SecretKey::from_mnemonic(&mnemonic, "").expect("Failed to restore key");
+        assert_ne!(sk, other);
+
+        // A wrong checksum or an unknown word is rejected.
+        let wrong_checksum = phrase.replace("about", "abandon");
+        assert_eq!(
+            Err(Error::InvalidMnemonic),
+            Mnemonic::parse(&wrong_checksum)
+        );
+        let unknown_word = phrase.replace("about", "abaut");
+        assert_eq!(Err(Error::InvalidMnemonic), Mnemonic::parse(&unknown_word));
+    }
+
+    #[test]
+    fn test_random_mnemonic() {
+        let mut rng = rand::thread_rng();
+        for &word_count in &[12, 15, 18, 21, 24] {
+            let mnemonic = Mnemonic::random(word_count, &mut rng).expect("Failed to create");
+            assert_eq!(word_count, mnemonic.word_count());
+            let restored = Mnemonic::parse(&mnemonic.phrase()).expect("Failed to parse mnemonic");
+            assert_eq!(mnemonic, restored);
+            let share =
+                SecretKeyShare::from_mnemonic(&mnemonic, "pass").expect("Failed to restore");
+            assert_eq!(Ok(share), SecretKeyShare::from_mnemonic(&restored, "pass"));
+        }
+        for &word_count in &[0, 11, 13, 27] {
+            let result = Mnemonic::random(word_count, &mut rng);
+            assert_eq!(Err(Error::InvalidMnemonic), result);
+        }
+    }
+}
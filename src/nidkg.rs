@@ -0,0 +1,616 @@
+//! Non-interactive distributed key generation with aggregatable transcripts.
+//!
+//! Each dealer publishes a single `Dealing`: a `Commitment` to a random polynomial `f` of degree
+//! `threshold`, and for each recipient `i`, the share `f(i + 1)` encrypted to the recipient's
+//! public key, together with a proof that the encryption is correct. Anyone can verify a dealing
+//! using only the public keys, and sum verified dealings into a `Transcript`. The transcript
+//! determines the `PublicKeySet`, and each recipient can decrypt their `SecretKeyShare` from it.
+//!
+//! To make this possible, the shares are encrypted bit by bit, using ElGamal "in the exponent":
+//! Bit `b` of a share is encrypted to the public key `pk = sk * g` as `(r * g, b * g + r * pk)`,
+//! with a random `r`. Each ciphertext comes with a zero-knowledge proof that it encrypts `0` or
+//! `1`, and a proof that the weighted sum of the ciphertexts encrypts the value `f(i + 1) * g`
+//! that can be computed from the commitment. Since the encryption is additively homomorphic, the
+//! ciphertexts of several dealings can be added: The sum of `k` dealings decrypts to values
+//! between `0` and `k` for each bit, which the recipient recovers by trying all of them.
+//!
+//! If all participants agree on a set of more than `threshold` valid dealings, e.g. the first ones
+//! that appear on a ledger, at least one of the dealers is correct, so no coalition of up to
+//! `threshold` participants knows the resulting master key.
+//!
+//! Each dealing is signed by its dealer: The dealer with index `d` signs with the secret key
+//! belonging to the `d`-th of the dealers' public keys, so that nobody can submit a dealing, or
+//! block a dealer from being included, in another dealer's name.
+
+use std::collections::BTreeSet;
+use std::mem::size_of_val;
+use std::slice;
+
+use byteorder::{BigEndian, ByteOrder};
+use memsec::memzero;
+use pairing::bls12_381::{Fr, G1};
+use pairing::{CurveAffine, CurveProjective, Field, PrimeField, PrimeFieldRepr};
+use rand::Rng;
+
+use super::error::{Error, Result};
+use super::poly::{Commitment, Poly};
+use super::{
+    hash_fr, serde_impl, IntoFr, PublicKey, PublicKeySet, SecretKey, SecretKeyShare, Signature,
+};
+
+/// The number of bits of a field element.
+const FR_BITS: usize = 255;
+
+/// The domain separation tag for the dealing proofs' challenge.
+const DEALING_DST: &[u8] = b"threshold_crypto NIDKG dealing";
+/// The domain separation tag for the dealer's signature.
+const SIGNATURE_DST: &[u8] = b"threshold_crypto NIDKG signature";
+
+/// A share, encrypted bit by bit to a recipient's public key. The `b`-th bit is encrypted as
+/// `(u[b], v[b])`.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct EncryptedShare {
+    #[serde(with = "serde_impl::projective_vec")]
+    u: Vec<G1>,
+    #[serde(with = "serde_impl::projective_vec")]
+    v: Vec<G1>,
+}
+
+impl EncryptedShare {
+    /// Returns `(sum(2^b * u[b]), sum(2^b * v[b]))`, the encryption of the share itself.
+    fn weighted_sum(&self) -> (G1, G1) {
+        let horner = |points: &[G1]| {
+            points.iter().rev().fold(G1::zero(), |mut acc, p| {
+                acc.double();
+                acc.add_assign(p);
+                acc
+            })
+        };
+        (horner(&self.u), horner(&self.v))
+    }
+
+    /// Adds the other encrypted share to this one.
+    fn add_assign(&mut self, other: &EncryptedShare) {
+        for (u, other_u) in self.u.iter_mut().zip(&other.u) {
+            u.add_assign(other_u);
+        }
+        for (v, other_v) in self.v.iter_mut().zip(&other.v) {
+            v.add_assign(other_v);
+        }
+    }
+
+    /// Returns `true` if the share has the right number of bits.
+    fn is_well_formed(&self) -> bool {
+        self.u.len() == FR_BITS && self.v.len() == FR_BITS
+    }
+}
+
+/// A proof that each of the bits of an `EncryptedShare` is `0` or `1`, and that they encrypt the
+/// share `s` with `s * g` determined by the commitment.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+struct ShareProof {
+    /// The challenges of the "bit is `0`" branches. The "bit is `1`" branches' challenges are the
+    /// differences to the dealing's challenge.
+    #[serde(with = "serde_impl::field_vec")]
+    c0: Vec<Fr>,
+    /// The responses of the "bit is `0`" branches.
+    #[serde(with = "serde_impl::field_vec")]
+    z0: Vec<Fr>,
+    /// The responses of the "bit is `1`" branches.
+    #[serde(with = "serde_impl::field_vec")]
+    z1: Vec<Fr>,
+    /// The response of the proof that the weighted sum encrypts the share.
+    #[serde(with = "serde_impl::field")]
+    z: Fr,
+}
+
+/// A dealer's contribution: a commitment to a random polynomial, and the encrypted shares with
+/// proofs of their correctness, signed by the dealer.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct Dealing {
+    dealer: u64,
+    commit: Commitment,
+    shares: Vec<EncryptedShare>,
+    proofs: Vec<ShareProof>,
+    #[serde(with = "serde_impl::field")]
+    challenge: Fr,
+    signature: Signature,
+}
+
+impl Dealing {
+    /// Creates a dealing by the dealer with the given index, for the recipients with the given
+    /// public keys, and signs it with the dealer's secret key. Recipient `i` receives key share
+    /// number `i`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn new<R: Rng>(
+        dealer: u64,
+        dealer_sk: &SecretKey,
+        pub_keys: &[PublicKey],
+        threshold: usize,
+        rng: &mut R,
+    ) -> Result<Dealing> {
+        let poly = Poly::random(threshold, rng)?;
+        let commit = poly.commitment();
+        let mut shares = Vec::with_capacity(pub_keys.len());
+        // The secrets and the proofs' random nonces for each recipient.
+        let mut witnesses = Vec::with_capacity(pub_keys.len());
+        let mut prover_commits = Vec::with_capacity(pub_keys.len());
+        for (i, pk) in pub_keys.iter().enumerate() {
+            let bits = fr_bits(&poly.evaluate(i + 1));
+            let (mut u, mut v) = (Vec::with_capacity(FR_BITS), Vec::with_capacity(FR_BITS));
+            let mut randomness = Vec::with_capacity(FR_BITS);
+            for &bit in &bits {
+                let r: Fr = rng.gen();
+                u.push(mul(G1::one(), &r));
+                let mut v_b = mul(pk.0, &r);
+                if bit {
+                    v_b.add_assign(&G1::one());
+                }
+                v.push(v_b);
+                randomness.push(r);
+            }
+            let share = EncryptedShare { u, v };
+
+            // For each bit, compute the commitments of the real branch, and simulate the other.
+            let mut bit_witnesses = Vec::with_capacity(FR_BITS);
+            let mut commits = Vec::with_capacity(4 * FR_BITS + 2);
+            for (b, &bit) in bits.iter().enumerate() {
+                let w: Fr = rng.gen();
+                let c_sim: Fr = rng.gen();
+                let z_sim: Fr = rng.gen();
+                let real = (mul(G1::one(), &w), mul(pk.0, &w));
+                let sim = branch_commits(pk, &share, b, !bit, &c_sim, &z_sim);
+                let (first, second) = if bit { (sim, real) } else { (real, sim) };
+                commits.extend(&[first.0, first.1, second.0, second.1]);
+                bit_witnesses.push((w, c_sim, z_sim));
+            }
+            // The weighted sum of the randomness `r`, which is the discrete logarithm of both
+            // `sum(2^b * u[b])` with respect to `g` and `sum(2^b * v[b]) - s * g` with respect to
+            // `pk`.
+            let r_sum = randomness.iter().rev().fold(Fr::zero(), |mut acc, r| {
+                acc.double();
+                acc.add_assign(r);
+                acc
+            });
+            let w_sum: Fr = rng.gen();
+            commits.push(mul(G1::one(), &w_sum));
+            commits.push(mul(pk.0, &w_sum));
+
+            shares.push(share);
+            witnesses.push((bits, randomness, bit_witnesses, r_sum, w_sum));
+            prover_commits.push(commits);
+        }
+
+        let challenge = dealing_challenge(dealer, &commit, &shares, &prover_commits);
+        let proofs = witnesses
+            .iter()
+            .map(|(bits, randomness, bit_witnesses, r_sum, w_sum)| {
+                let mut proof = ShareProof {
+                    c0: Vec::with_capacity(FR_BITS),
+                    z0: Vec::with_capacity(FR_BITS),
+                    z1: Vec::with_capacity(FR_BITS),
+                    z: response(*w_sum, &challenge, r_sum),
+                };
+                for ((&bit, r), &(w, c_sim, z_sim)) in
+                    bits.iter().zip(randomness).zip(bit_witnesses)
+                {
+                    let mut c_real = challenge;
+                    c_real.sub_assign(&c_sim);
+                    let z_real = response(w, &c_real, r);
+                    if bit {
+                        proof.c0.push(c_sim);
+                        proof.z0.push(z_sim);
+                        proof.z1.push(z_real);
+                    } else {
+                        proof.c0.push(c_real);
+                        proof.z0.push(z_real);
+                        proof.z1.push(z_sim);
+                    }
+                }
+                proof
+            })
+            .collect();
+        for (bits, randomness, bit_witnesses, r_sum, w_sum) in &mut witnesses {
+            clear(bits);
+            clear(randomness);
+            clear(bit_witnesses);
+            clear(slice::from_mut(r_sum));
+            clear(slice::from_mut(w_sum));
+        }
+
+        let signature = dealer_sk.sign(signed_message(dealer, &challenge));
+        Ok(Dealing {
+            dealer,
+            commit,
+            shares,
+            proofs,
+            challenge,
+            signature,
+        })
+    }
+
+    /// Returns the index of the dealer.
+    pub fn dealer(&self) -> u64 {
+        self.dealer
+    }
+
+    /// Returns `true` if the dealing is signed by its dealer, whose public key is the dealer's
+    /// index in `dealer_pks`.
+    pub fn verify_signature(&self, dealer_pks: &[PublicKey]) -> bool {
+        let msg = signed_message(self.dealer, &self.challenge);
+        dealer_pks
+            .get(self.dealer as usize)
+            .filter(|pk| pk.verify(&self.signature, msg))
+            .is_some()
+    }
+
+    /// Returns the commitment to the dealer's polynomial.
+    pub fn commitment(&self) -> &Commitment {
+        &self.commit
+    }
+
+    /// Returns `true` if the dealing is signed by its dealer, and contains a correctly encrypted
+    /// share of a polynomial of degree `threshold` for each of the recipients with the given
+    /// public keys.
+    pub fn verify(
+        &self,
+        dealer_pks: &[PublicKey],
+        pub_keys: &[PublicKey],
+        threshold: usize,
+    ) -> bool {
+        if !self.verify_signature(dealer_pks)
+            || self.commit.coeff.len() != threshold + 1
+            || self.shares.len() != pub_keys.len()
+            || self.proofs.len() != pub_keys.len()
+        {
+            return false;
+        }
+        let mut verifier_commits = Vec::with_capacity(pub_keys.len());
+        for (i, ((pk, share), proof)) in pub_keys
+            .iter()
+            .zip(&self.shares)
+            .zip(&self.proofs)
+            .enumerate()
+        {
+            if !share.is_well_formed()
+                || proof.c0.len() != FR_BITS
+                || proof.z0.len() != FR_BITS
+                || proof.z1.len() != FR_BITS
+            {
+                return false;
+            }
+            let mut commits = Vec::with_capacity(4 * FR_BITS + 2);
+            for b in 0..FR_BITS {
+                let c0 = proof.c0[b];
+                let mut c1 = self.challenge;
+                c1.sub_assign(&c0);
+                let first = branch_commits(pk, share, b, false, &c0, &proof.z0[b]);
+                let second = branch_commits(pk, share, b, true, &c1, &proof.z1[b]);
+                commits.extend(&[first.0, first.1, second.0, second.1]);
+            }
+            let (u_sum, mut v_sum) = share.weighted_sum();
+            v_sum.sub_assign(&self.commit.evaluate(i + 1));
+            let mut com_u = mul(u_sum, &self.challenge);
+            com_u.add_assign(&mul(G1::one(), &proof.z));
+            let mut com_v = mul(v_sum, &self.challenge);
+            com_v.add_assign(&mul(pk.0, &proof.z));
+            commits.push(com_u);
+            commits.push(com_v);
+            verifier_commits.push(commits);
+        }
+        self.challenge
+            == dealing_challenge(self.dealer, &self.commit, &self.shares, &verifier_commits)
+    }
+}
+
+/// The sum of several dealings. It determines the public key set, and each recipient can decrypt
+/// their secret key share from it.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct Transcript {
+    dealers: BTreeSet<u64>,
+    commit: Commitment,
+    shares: Vec<EncryptedShare>,
+}
+
+impl Transcript {
+    /// Returns the transcript containing only the given dealing.
+    ///
+    /// Only the dealer's signature is verified: Use `Dealing::verify` to check the shares first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidSignature` if the dealing is not signed by its dealer, whose
+    /// public key is the dealer's index in `dealer_pks`.
+    pub fn new(dealing: &Dealing, dealer_pks: &[PublicKey]) -> Result<Transcript> {
+        if !dealing.verify_signature(dealer_pks) {
+            return Err(Error::InvalidSignature);
+        }
+        Ok(Transcript {
+            dealers: Some(dealing.dealer).into_iter().collect(),
+            commit: dealing.commit.clone(),
+            shares: dealing.shares.clone(),
+        })
+    }
+
+    /// Returns the sum of the given dealings.
+    ///
+    /// Only the dealers' signatures are verified: Use `Dealing::verify` to check the shares first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::NotEnoughShares` if there are no dealings, and the errors of `new` and
+    /// `merge`.
+    pub fn aggregate<'a, I>(dealings: I, dealer_pks: &[PublicKey]) -> Result<Transcript>
+    where
+        I: IntoIterator<Item = &'a Dealing>,
+    {
+        let mut dealings = dealings.into_iter();
+        let mut transcript = match dealings.next() {
+            Some(dealing) => Transcript::new(dealing, dealer_pks)?,
+            None => return Err(Error::NotEnoughShares),
+        };
+        for dealing in dealings {
+            transcript.merge(&Transcript::new(dealing, dealer_pks)?)?;
+        }
+        Ok(transcript)
+    }
+
+    /// Adds the other transcript to this one. Since every dealing in a transcript created by
+    /// `new` or `aggregate` is signed by its dealer, each dealer can only be included once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::DuplicateEntry` if a dealer is included in both transcripts, and an
+    /// `Error::InvalidShare` if they are for a different number of recipients.
+    pub fn merge(&mut self, other: &Transcript) -> Result<()> {
+        if !self.dealers.is_disjoint(&other.dealers) {
+            return Err(Error::DuplicateEntry);
+        }
+        if self.shares.len() != other.shares.len() {
+            return Err(Error::InvalidShare);
+        }
+        self.dealers.extend(&other.dealers);
+        self.commit += &other.commit;
+        for (share, other_share) in self.shares.iter_mut().zip(&other.shares) {
+            share.add_assign(other_share);
+        }
+        Ok(())
+    }
+
+    /// Returns the indices of the dealers whose dealings are included.
+    pub fn dealers(&self) -> &BTreeSet<u64> {
+        &self.dealers
+    }
+
+    /// Returns the resulting public key set.
+    pub fn public_keys(&self) -> PublicKeySet {
+        PublicKeySet::from(self.commit.clone())
+    }
+
+    /// Decrypts the `i`-th secret key share, using the `i`-th recipient's secret key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidShare` if the share can't be decrypted or doesn't match the
+    /// public key set, which can only happen if the transcript contains invalid dealings or the
+    /// wrong secret key is used, and an `Error::MlockFailed` if we have reached the systems's
+    /// locked memory limit.
+    pub fn decrypt_share(&self, i: usize, sk: &SecretKey) -> Result<SecretKeyShare> {
+        let share = self.shares.get(i).ok_or(Error::InvalidShare)?;
+        // Each bit of the sum is between `0` and the number of dealers.
+        let mut value = Fr::zero();
+        for (u, v) in share.u.iter().zip(&share.v).rev() {
+            let mut digit_g = *v;
+            digit_g.sub_assign(&mul(*u, &sk.0));
+            let mut candidate = G1::zero();
+            let mut digit = 0;
+            while candidate != digit_g {
+                if digit == self.dealers.len() {
+                    return Err(Error::InvalidShare);
+                }
+                candidate.add_assign(&G1::one());
+                digit += 1;
+            }
+            value.double();
+            value.add_assign(&digit.into_fr());
+        }
+        let sk_share = SecretKeyShare::from_mut_ptr(&mut value as *mut Fr)?;
+        if sk_share.public_key_share() != self.public_keys().public_key_share(i) {
+            return Err(Error::InvalidShare);
+        }
+        Ok(sk_share)
+    }
+}
+
+/// Returns the prover's commitments `(z * g + c * u[b], z * pk + c * (v[b] - bit * g))` for the
+/// branch of the proof that bit `b` has the given value.
+fn branch_commits(
+    pk: &PublicKey,
+    share: &EncryptedShare,
+    b: usize,
+    bit: bool,
+    c: &Fr,
+    z: &Fr,
+) -> (G1, G1) {
+    let mut com_u = mul(share.u[b], c);
+    com_u.add_assign(&mul(G1::one(), z));
+    let mut v = share.v[b];
+    if bit {
+        v.sub_assign(&G1::one());
+    }
+    let mut com_v = mul(v, c);
+    com_v.add_assign(&mul(pk.0, z));
+    (com_u, com_v)
+}
+
+/// Returns the challenge for all of a dealing's proofs.
+fn dealing_challenge(
+    dealer: u64,
+    commit: &Commitment,
+    shares: &[EncryptedShare],
+    prover_commits: &[Vec<G1>],
+) -> Fr {
+    let mut msg = DEALING_DST.to_vec();
+    let mut dealer_bytes = [0; 8];
+    BigEndian::write_u64(&mut dealer_bytes, dealer);
+    msg.extend(&dealer_bytes);
+    let shares_points = shares
+        .iter()
+        .flat_map(|share| share.u.iter().chain(&share.v));
+    for point in commit
+        .coeff
+        .iter()
+        .chain(shares_points)
+        .chain(prover_commits.iter().flatten())
+    {
+        msg.extend(point.into_affine().into_compressed().as_ref());
+    }
+    hash_fr(&msg)
+}
+
+/// Returns the message that the dealer signs: the dealer's index and the dealing's challenge,
+/// which is a hash of all of its contents.
+fn signed_message(dealer: u64, challenge: &Fr) -> Vec<u8> {
+    let mut msg = SIGNATURE_DST.to_vec();
+    let mut dealer_bytes = [0; 8];
+    BigEndian::write_u64(&mut dealer_bytes, dealer);
+    msg.extend(&dealer_bytes);
+    challenge
+        .into_repr()
+        .write_be(&mut msg)
+        .expect("writing to a vector never fails");
+    msg
+}
+
+/// Overwrites the secret values with zeros.
+fn clear<T>(values: &mut [T]) {
+    unsafe {
+        memzero(values.as_mut_ptr() as *mut u8, size_of_val(values));
+    }
+}
+
+/// Returns the `FR_BITS` bits of the field element, least significant first.
+fn fr_bits(fr: &Fr) -> Vec<bool> {
+    let repr = fr.into_repr();
+    (0..FR_BITS)
+        .map(|b| (repr.as_ref()[b / 64] >> (b % 64)) & 1 == 1)
+        .collect()
+}
+
+/// Returns the product of the group element and the scalar.
+fn mul(mut point: G1, scalar: &Fr) -> G1 {
+    point.mul_assign(*scalar);
+    point
+}
+
+/// Returns the Schnorr response `w - c * x`.
+fn response(mut w: Fr, c: &Fr, x: &Fr) -> Fr {
+    let mut cx = *c;
+    cx.mul_assign(x);
+    w.sub_assign(&cx);
+    w
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{self, random};
+
+    use super::*;
+
+    #[test]
+    fn test_aggregate_dealings() {
+        let mut rng = rand::thread_rng();
+        let threshold = 1;
+        let sec_keys: Vec<SecretKey> = (0..3).map(|_| random()).collect();
+        let pub_keys: Vec<_> = sec_keys.iter().map(SecretKey::public_key).collect();
+
+        // Dealers 0 and 2 publish their dealings, which anyone can verify. The recipients are also
+        // the dealers, and sign with the same keys.
+        let dealings: Vec<_> = [0, 2]
+            .iter()
+            .map(|&dealer| {
+                let sk = &sec_keys[dealer as usize];
+                let dealing = Dealing::new(dealer, sk, &pub_keys, threshold, &mut rng)
+                    .expect("Failed to create dealing");
+                assert!(dealing.verify(&pub_keys, &pub_keys, threshold));
+                assert!(!dealing.verify(&pub_keys, &pub_keys, threshold + 1));
+                assert!(!dealing.verify(&pub_keys, &pub_keys[..2], threshold));
+                dealing
+            })
+            .collect();
+        let transcript = Transcript::aggregate(&dealings, &pub_keys).expect("Failed to aggregate");
+        assert_eq!(2, transcript.dealers().len());
+        let pk_set = transcript.public_keys();
+        assert_eq!(threshold, pk_set.threshold());
+        let expected_pk = dealings[0].commitment() + dealings[1].commitment();
+        assert_eq!(PublicKeySet::from(expected_pk), pk_set);
+
+        // Each recipient decrypts their share, and they can sign together.
+        let msg = "Agreed on without an interactive round";
+        let sig_shares: Vec<_> = sec_keys
+            .iter()
+            .enumerate()
+            .map(|(i, sk)| {
+                let sk_share = transcript
+                    .decrypt_share(i, sk)
+                    .expect("Failed to decrypt share");
+                (i, sk_share.sign(msg))
+            })
+            .collect();
+        let sig = pk_set
+            .combine_signatures(sig_shares.iter().map(|(i, sig)| (*i, sig)))
+            .expect("Failed to combine signatures");
+        assert!(pk_set.public_key().verify(&sig, msg));
+
+        // The wrong key can't decrypt the share.
+        assert!(transcript.decrypt_share(0, &sec_keys[1]).is_err());
+
+        // Each dealer can only be included once.
+        let mut transcript0 = Transcript::new(&dealings[0], &pub_keys).expect("valid signature");
+        let transcript0_copy = transcript0.clone();
+        assert_eq!(
+            Err(Error::DuplicateEntry),
+            transcript0.merge(&transcript0_copy)
+        );
+    }
+
+    #[test]
+    fn test_invalid_dealing() {
+        let mut rng = rand::thread_rng();
+        let sec_keys: Vec<SecretKey> = (0..2).map(|_| random()).collect();
+        let pub_keys: Vec<_> = sec_keys.iter().map(SecretKey::public_key).collect();
+        let dealing = Dealing::new(0, &sec_keys[0], &pub_keys, 1, &mut rng)
+            .expect("Failed to create dealing");
+        assert!(dealing.verify(&pub_keys, &pub_keys, 1));
+
+        // A tampered bit, commitment or dealer index is detected.
+        let mut tampered = dealing.clone();
+        tampered.shares[1].v[7].add_assign(&G1::one());
+        assert!(!tampered.verify(&pub_keys, &pub_keys, 1));
+        let mut tampered = dealing.clone();
+        tampered.commit.coeff[0].add_assign(&G1::one());
+        assert!(!tampered.verify(&pub_keys, &pub_keys, 1));
+        let mut tampered = dealing.clone();
+        tampered.dealer = 1;
+        assert!(!tampered.verify(&pub_keys, &pub_keys, 1));
+
+        // Shares encrypted to the wrong recipients are detected.
+        let mut swapped = dealing.clone();
+        swapped.shares.swap(0, 1);
+        swapped.proofs.swap(0, 1);
+        assert!(!swapped.verify(&pub_keys, &pub_keys, 1));
+
+        // A dealing in another dealer's name, with a valid proof but signed with the wrong key,
+        // is rejected, and can't be added to a transcript.
+        let forged = Dealing::new(1, &sec_keys[0], &pub_keys, 1, &mut rng)
+            .expect("Failed to create dealing");
+        assert!(!forged.verify(&pub_keys, &pub_keys, 1));
+        assert_eq!(
+            Err(Error::InvalidSignature),
+            Transcript::aggregate(&[dealing, forged], &pub_keys)
+        );
+    }
+}
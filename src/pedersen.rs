@@ -0,0 +1,310 @@
+//! Pedersen verifiable secret sharing.
+//!
+//! A `Commitment` publishes `c * g` for each coefficient `c` of the secret polynomial. Its
+//! constant term is `secret * g`, so it reveals the public key, and it only hides the secret as
+//! long as discrete logarithms are hard to compute. A `PedersenCommitment` instead publishes
+//! `c * g + d * h` for each coefficient `c`, where `d` is the corresponding coefficient of a
+//! random _blinding_ polynomial, and `h` is a second generator whose discrete logarithm with
+//! respect to `g` is unknown. This reveals no information at all about the secret, even to an
+//! adversary with unlimited computing power.
+//!
+//! Each share consists of the values of both polynomials, and can be verified against the
+//! commitment just like a share of a Feldman commitment. Shares can be sent to their recipients
+//! wrapped in a `SerdeSecret`.
+
+use std::borrow::Borrow;
+use std::hash::{Hash, Hasher};
+use std::ops;
+
+use pairing::bls12_381::{Fr, G1};
+use pairing::{CurveAffine, CurveProjective};
+use rand::Rng;
+
+use super::error::Result;
+use super::poly::Poly;
+use super::{hash_g1, into_fr_plus_1, serde_impl, IntoFr, SecretKeyShare};
+
+/// The string hashed to `G1` to obtain the blinding generator `h`.
+const GENERATOR_DST: &[u8] = b"threshold_crypto Pedersen blinding generator";
+
+lazy_static! {
+    /// The blinding generator `h`. Its discrete logarithm with respect to `g` is unknown, since it
+    /// is derived by hashing a fixed string.
+    static ref H: G1 = hash_g1(GENERATOR_DST);
+}
+
+/// Returns the blinding generator `h`.
+pub fn blinding_generator() -> G1 {
+    *H
+}
+
+/// A Pedersen commitment to a polynomial and a blinding polynomial.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PedersenCommitment {
+    /// The commitments `c * g + d * h` to the coefficients.
+    #[serde(with = "serde_impl::projective_vec")]
+    coeff: Vec<G1>,
+}
+
+impl Hash for PedersenCommitment {
+    fn hash<S: Hasher>(&self, state: &mut S) {
+        self.coeff.len().hash(state);
+        for c in &self.coeff {
+            c.into_affine().into_compressed().as_ref().hash(state);
+        }
+    }
+}
+
+impl<B: Borrow<PedersenCommitment>> ops::AddAssign<B> for PedersenCommitment {
+    fn add_assign(&mut self, rhs: B) {
+        let rhs = rhs.borrow();
+        if self.coeff.len() < rhs.coeff.len() {
+            self.coeff.resize(rhs.coeff.len(), G1::zero());
+        }
+        for (self_c, rhs_c) in self.coeff.iter_mut().zip(&rhs.coeff) {
+            self_c.add_assign(rhs_c);
+        }
+    }
+}
+
+impl<B: Borrow<PedersenCommitment>> ops::Add<B> for &PedersenCommitment {
+    type Output = PedersenCommitment;
+
+    fn add(self, rhs: B) -> PedersenCommitment {
+        (*self).clone() + rhs
+    }
+}
+
+impl<B: Borrow<PedersenCommitment>> ops::Add<B> for PedersenCommitment {
+    type Output = PedersenCommitment;
+
+    fn add(mut self, rhs: B) -> PedersenCommitment {
+        self += rhs;
+        self
+    }
+}
+
+impl PedersenCommitment {
+    /// Returns the polynomials' degree.
+    pub fn degree(&self) -> usize {
+        self.coeff.len() - 1
+    }
+
+    /// Returns the commitment's value at the point `i`: `f(i) * g + b(i) * h`.
+    pub fn evaluate<T: IntoFr>(&self, i: T) -> G1 {
+        let mut result = match self.coeff.last() {
+            None => return G1::zero(),
+            Some(c) => *c,
+        };
+        let x = i.into_fr();
+        for c in self.coeff.iter().rev().skip(1) {
+            result.mul_assign(x);
+            result.add_assign(c);
+        }
+        result
+    }
+
+    /// Returns `true` if the share matches the `i`-th value of the committed polynomials.
+    pub fn verify_share<T: IntoFr>(&self, i: T, share: &PedersenShare) -> bool {
+        self.evaluate(into_fr_plus_1(i)) == share.commitment()
+    }
+}
+
+impl Poly {
+    /// Returns the Pedersen commitment to this polynomial, blinded with the given one.
+    pub fn pedersen_commitment(&self, blinding: &Poly) -> PedersenCommitment {
+        let len = ::std::cmp::max(self.coeff.len(), blinding.coeff.len());
+        let coeff = (0..len)
+            .map(|i| {
+                let mut c = G1::zero();
+                if let Some(a) = self.coeff.get(i) {
+                    c.add_assign(&G1::one().into_affine().mul(*a));
+                }
+                if let Some(b) = blinding.coeff.get(i) {
+                    c.add_assign(&H.into_affine().mul(*b));
+                }
+                c
+            })
+            .collect();
+        PedersenCommitment { coeff }
+    }
+}
+
+/// A secret polynomial and a blinding polynomial, for Pedersen verifiable secret sharing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PedersenSecretKeySet {
+    /// The polynomial whose value at `0` is the secret, and at `i + 1` is share number `i`.
+    poly: Poly,
+    /// The polynomial that hides `poly` in the commitment.
+    blinding: Poly,
+}
+
+impl PedersenSecretKeySet {
+    /// Creates a random secret and blinding polynomial, so that any `threshold + 1` shares can
+    /// reconstruct the secret.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn random<R: Rng>(threshold: usize, rng: &mut R) -> Result<Self> {
+        let poly = Poly::random(threshold, rng)?;
+        let blinding = Poly::random(threshold, rng)?;
+        Ok(PedersenSecretKeySet { poly, blinding })
+    }
+
+    /// Creates a set of shares of the given polynomial, with a random blinding polynomial of the
+    /// same degree.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn from_poly<R: Rng>(poly: Poly, rng: &mut R) -> Result<Self> {
+        let blinding = Poly::random(poly.degree(), rng)?;
+        Ok(PedersenSecretKeySet { poly, blinding })
+    }
+
+    /// Returns the threshold `t`: any set of `t + 1` shares can reconstruct the secret.
+    pub fn threshold(&self) -> usize {
+        self.poly.degree()
+    }
+
+    /// Returns the `i`-th share, consisting of the `i`-th secret key share and blinding value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn share<T: IntoFr>(&self, i: T) -> Result<PedersenShare> {
+        let mut value = self.poly.evaluate(into_fr_plus_1(i));
+        let mut blinding = self.blinding.evaluate(into_fr_plus_1(i));
+        Ok(PedersenShare {
+            value: SecretKeyShare::from_mut_ptr(&mut value as *mut Fr)?,
+            blinding: SecretKeyShare::from_mut_ptr(&mut blinding as *mut Fr)?,
+        })
+    }
+
+    /// Returns the commitment, which can be published without revealing anything about the
+    /// secret.
+    pub fn commitment(&self) -> PedersenCommitment {
+        self.poly.pedersen_commitment(&self.blinding)
+    }
+}
+
+/// A share of a Pedersen secret key set: a secret key share, and the blinding value that hides it
+/// in the commitment.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PedersenShare {
+    value: SecretKeyShare,
+    blinding: SecretKeyShare,
+}
+
+impl PedersenShare {
+    /// Creates a share from a secret key share and its blinding value.
+    pub fn new(value: SecretKeyShare, blinding: SecretKeyShare) -> Self {
+        PedersenShare { value, blinding }
+    }
+
+    /// Returns the secret key share.
+    pub fn secret_key_share(&self) -> &SecretKeyShare {
+        &self.value
+    }
+
+    /// Returns the blinding value.
+    pub fn blinding(&self) -> &SecretKeyShare {
+        &self.blinding
+    }
+
+    /// Returns the share's commitment `value * g + blinding * h`.
+    pub fn commitment(&self) -> G1 {
+        let mut result = G1::one().into_affine().mul(*(self.value.0).0);
+        result.add_assign(&H.into_affine().mul(*(self.blinding.0).0));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use bincode;
+    use rand;
+
+    use super::super::{SecretKeySet, SerdeSecret};
+    use super::*;
+
+    #[test]
+    fn test_pedersen_vss() {
+        let mut rng = rand::thread_rng();
+        let sk_set = PedersenSecretKeySet::random(2, &mut rng).expect("Failed to create key set");
+        let commit = sk_set.commitment();
+        assert_eq!(2, commit.degree());
+
+        // Each share matches the commitment, but not at another index.
+        let shares: Vec<_> = (0..5)
+            .map(|i| sk_set.share(i).expect("Failed to create share"))
+            .collect();
+        for (i, share) in shares.iter().enumerate() {
+            assert!(commit.verify_share(i, share));
+            assert!(!commit.verify_share(i + 1, share));
+        }
+
+        // The commitment doesn't reveal the public key.
+        let pk_set = SecretKeySet::from(sk_set.poly.clone()).public_keys();
+        assert_ne!(pk_set.public_key().0, commit.evaluate(0));
+
+        // The secret key shares can sign together.
+        let msg = "Shared with hiding commitments";
+        let sig_shares: BTreeMap<_, _> = [0, 2, 4]
+            .iter()
+            .map(|&i| (i, shares[i].secret_key_share().sign(msg)))
+            .collect();
+        let sig = pk_set
+            .combine_signatures(&sig_shares)
+            .expect("Failed to combine signatures");
+        assert!(pk_set.public_key().verify(&sig, msg));
+
+        // A share with a modified value or blinding doesn't match.
+        let mut wrong = shares[1].clone();
+        wrong.value = shares[2].value.clone();
+        assert!(!commit.verify_share(1, &wrong));
+        let mut wrong = shares[1].clone();
+        wrong.blinding = shares[2].blinding.clone();
+        assert!(!commit.verify_share(1, &wrong));
+    }
+
+    #[test]
+    fn test_pedersen_serde() {
+        let mut rng = rand::thread_rng();
+        let sk_set = PedersenSecretKeySet::random(2, &mut rng).expect("Failed to create key set");
+        let share = sk_set.share(3).expect("Failed to create share");
+
+        // The recipient deserializes the commitment and their share, and verifies it.
+        let ser_commit = bincode::serialize(&sk_set.commitment()).expect("serialize commitment");
+        let ser_share = bincode::serialize(&SerdeSecret(&share)).expect("serialize share");
+        let commit: PedersenCommitment =
+            bincode::deserialize(&ser_commit).expect("deserialize commitment");
+        let de_share: SerdeSecret<PedersenShare> =
+            bincode::deserialize(&ser_share).expect("deserialize share");
+        assert_eq!(share, *de_share);
+        assert!(commit.verify_share(3, &de_share));
+        assert!(!commit.verify_share(2, &de_share));
+
+        // A share can also be assembled from its parts.
+        let rebuilt =
+            PedersenShare::new(share.secret_key_share().clone(), share.blinding().clone());
+        assert!(commit.verify_share(3, &rebuilt));
+    }
+
+    #[test]
+    fn test_pedersen_commitment_addition() {
+        let mut rng = rand::thread_rng();
+        let set0 = PedersenSecretKeySet::random(1, &mut rng).expect("Failed to create key set");
+        let set1 = PedersenSecretKeySet::random(1, &mut rng).expect("Failed to create key set");
+        let sum = PedersenSecretKeySet {
+            poly: set0.poly.clone() + &set1.poly,
+            blinding: set0.blinding.clone() + &set1.blinding,
+        };
+        assert_eq!(sum.commitment(), set0.commitment() + set1.commitment());
+        let share = sum.share(3).expect("Failed to create share");
+        assert!((&set0.commitment() + set1.commitment()).verify_share(3, &share));
+    }
+}
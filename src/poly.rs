@@ -249,10 +249,8 @@ impl<B: Borrow<Self>> ops::MulAssign<B> for Poly {
 ///
 /// This operation may panic if: when multiplying the polynomial by a zero field element, we fail
 /// to munlock the cleared `coeff` vector.
-impl<'a> ops::Mul<Fr> for Poly {
-    type Output = Poly;
-
-    fn mul(mut self, rhs: Fr) -> Self::Output {
+impl ops::MulAssign<Fr> for Poly {
+    fn mul_assign(&mut self, rhs: Fr) {
         if rhs.is_zero() {
             self.zero_secret_memory();
             if let Err(e) = self.munlock_secret_memory() {
@@ -262,6 +260,18 @@ impl<'a> ops::Mul<Fr> for Poly {
         } else {
             self.coeff.iter_mut().for_each(|c| c.mul_assign(&rhs));
         }
+    }
+}
+
+/// # Panics
+///
+/// This operation may panic if: when multiplying the polynomial by a zero field element, we fail
+/// to munlock the cleared `coeff` vector.
+impl<'a> ops::Mul<Fr> for Poly {
+    type Output = Poly;
+
+    fn mul(mut self, rhs: Fr) -> Self::Output {
+        self *= rhs;
         self
     }
 }
@@ -618,6 +628,58 @@ impl<B: Borrow<Commitment>> ops::Add<B> for Commitment {
     }
 }
 
+impl<B: Borrow<Commitment>> ops::SubAssign<B> for Commitment {
+    fn sub_assign(&mut self, rhs: B) {
+        let len = cmp::max(self.coeff.len(), rhs.borrow().coeff.len());
+        self.coeff.resize(len, G1::zero());
+        for (self_c, rhs_c) in self.coeff.iter_mut().zip(&rhs.borrow().coeff) {
+            self_c.sub_assign(rhs_c);
+        }
+        self.remove_zeros();
+    }
+}
+
+impl<B: Borrow<Commitment>> ops::Sub<B> for &Commitment {
+    type Output = Commitment;
+
+    fn sub(self, rhs: B) -> Commitment {
+        (*self).clone() - rhs
+    }
+}
+
+impl<B: Borrow<Commitment>> ops::Sub<B> for Commitment {
+    type Output = Commitment;
+
+    fn sub(mut self, rhs: B) -> Commitment {
+        self -= rhs;
+        self
+    }
+}
+
+impl ops::MulAssign<Fr> for Commitment {
+    fn mul_assign(&mut self, rhs: Fr) {
+        self.coeff.iter_mut().for_each(|c| c.mul_assign(rhs));
+        self.remove_zeros();
+    }
+}
+
+impl ops::Mul<Fr> for &Commitment {
+    type Output = Commitment;
+
+    fn mul(self, rhs: Fr) -> Commitment {
+        (*self).clone() * rhs
+    }
+}
+
+impl ops::Mul<Fr> for Commitment {
+    type Output = Commitment;
+
+    fn mul(mut self, rhs: Fr) -> Commitment {
+        self *= rhs;
+        self
+    }
+}
+
 impl Commitment {
     /// Returns the polynomial's degree.
     pub fn degree(&self) -> usize {
@@ -638,10 +700,47 @@ impl Commitment {
         result
     }
 
-    /// Removes all trailing zero coefficients.
+    /// Returns the unique commitment of degree `samples.len() - 1` with the given values
+    /// `(x, f(x) * g)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::DuplicateEntry` if two samples have the same `x`.
+    pub(crate) fn interpolate(samples: &[(Fr, G1)]) -> Result<Self> {
+        let mut coeff = vec![G1::zero(); samples.len()];
+        for (i, &(x, y)) in samples.iter().enumerate() {
+            // Compute the coefficients of the Lagrange polynomial that is `1` at `x` and `0` at
+            // the other points.
+            let mut lagrange = vec![Fr::one()];
+            let mut denom = Fr::one();
+            for &(x0, _) in samples.iter().take(i).chain(samples.iter().skip(i + 1)) {
+                let mut product = vec![Fr::zero(); lagrange.len() + 1];
+                for (k, c) in lagrange.iter().enumerate() {
+                    product[k + 1].add_assign(c);
+                    let mut term = *c;
+                    term.mul_assign(&x0);
+                    product[k].sub_assign(&term);
+                }
+                lagrange = product;
+                let mut diff = x;
+                diff.sub_assign(&x0);
+                denom.mul_assign(&diff);
+            }
+            let denom_inv = denom.inverse().ok_or(Error::DuplicateEntry)?;
+            for (c, l) in coeff.iter_mut().zip(lagrange) {
+                let mut term = y;
+                term.mul_assign(l);
+                term.mul_assign(denom_inv);
+                c.add_assign(&term);
+            }
+        }
+        Ok(Commitment { coeff })
+    }
+
+    /// Removes all trailing zero coefficients, except for the constant term.
     fn remove_zeros(&mut self) {
         let zeros = self.coeff.iter().rev().take_while(|c| c.is_zero()).count();
-        let len = self.coeff.len() - zeros;
+        let len = cmp::max(self.coeff.len() - zeros, 1);
         self.coeff.truncate(len)
     }
 }
@@ -875,6 +974,12 @@ impl BivarCommitment {
         result
     }
 
+    /// Returns `true` if the number of coefficients matches the degree. This can only be violated
+    /// by a commitment that was deserialized from untrusted input.
+    pub(crate) fn is_well_formed(&self) -> bool {
+        self.coeff.len() == coeff_pos(self.degree + 1, 0)
+    }
+
     /// Returns the `x`-th row, as a commitment to a univariate polynomial.
     pub fn row<T: IntoFr>(&self, x: T) -> Commitment {
         let x_pow = self.powers(x);
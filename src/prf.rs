@@ -0,0 +1,213 @@
+//! A threshold pseudorandom function, in the style of DiSE.
+//!
+//! The PRF's value at an input `x` is `H2(x, k * H1(x))`, where `k` is the secret key, `H1` hashes
+//! to `G2` and `H2` hashes to a byte string of the requested length. The key is never
+//! reconstructed: Each secret key share holder computes a partial evaluation `k_i * H1(x)`, which
+//! can be verified against their public key share, and any `threshold + 1` of them combine into
+//! `k * H1(x)`.
+//!
+//! This allows a committee to e.g. derive a separate data-encryption key for every object without
+//! any single node being able to compute them.
+//!
+//! `H1` is the IETF hash-to-curve function with the PRF's own domain separation tag, so signing
+//! any message doesn't reveal a PRF value. However, signing an arbitrary element of `G2` does: The
+//! key set must not be used for blind signatures, or with `sign_g2` on points chosen by others.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use byteorder::{BigEndian, ByteOrder};
+use pairing::bls12_381::{Bls12, G1Affine, G2};
+use pairing::{CurveAffine, CurveProjective, Engine};
+use tiny_keccak::sha3_256;
+
+use super::{
+    hash_to_g2, interpolate, serde_impl, HexBytes, IntoFr, PublicKeySet, PublicKeyShare, Result,
+    SecretKey, SecretKeyShare,
+};
+
+/// The domain separation tag for hashing PRF inputs to `G2`.
+const INPUT_DST: &[u8] = b"THRESHOLD_CRYPTO_PRF_BLS12381G2_XMD:SHA-256_SSWU_RO_";
+
+/// The domain separation tag for hashing the PRF's group element to its output.
+const OUTPUT_DST: &[u8] = b"threshold_crypto PRF output";
+
+/// A partial evaluation of the PRF. A threshold of them can be combined into the PRF's value.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Rand)]
+pub struct PrfShare(#[serde(with = "serde_impl::projective")] G2);
+
+impl fmt::Debug for PrfShare {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let uncomp = self.0.into_affine().into_uncompressed();
+        write!(f, "PrfShare({:?})", HexBytes(uncomp.as_ref()))
+    }
+}
+
+impl Hash for PrfShare {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.into_affine().into_compressed().as_ref().hash(state);
+    }
+}
+
+impl SecretKey {
+    /// Evaluates the PRF with this key, and returns `len` bytes of output.
+    pub fn prf<M: AsRef<[u8]>>(&self, input: M, len: usize) -> Vec<u8> {
+        let g2 = hash_prf_input(&input).into_affine().mul(*self.0);
+        prf_output(input.as_ref(), g2, len)
+    }
+}
+
+impl SecretKeyShare {
+    /// Returns this share's partial evaluation of the PRF at the given input.
+    pub fn prf_share<M: AsRef<[u8]>>(&self, input: M) -> PrfShare {
+        PrfShare(hash_prf_input(input).into_affine().mul(*(self.0).0))
+    }
+}
+
+impl PublicKeyShare {
+    /// Returns `true` if the partial evaluation was computed correctly by the owner of the
+    /// corresponding secret key share.
+    pub fn verify_prf_share<M: AsRef<[u8]>>(&self, input: M, share: &PrfShare) -> bool {
+        let hash = hash_prf_input(input);
+        Bls12::pairing((self.0).0, hash) == Bls12::pairing(G1Affine::one(), share.0)
+    }
+}
+
+impl PublicKeySet {
+    /// Combines the partial evaluations into the PRF's value at the given input, and returns
+    /// `len` bytes of output.
+    ///
+    /// The shares are not verified: Use `PublicKeyShare::verify_prf_share` to check them first.
+    pub fn combine_prf_shares<'a, M, T, I>(
+        &self,
+        input: M,
+        shares: I,
+        len: usize,
+    ) -> Result<Vec<u8>>
+    where
+        M: AsRef<[u8]>,
+        I: IntoIterator<Item = (T, &'a PrfShare)>,
+        T: IntoFr,
+    {
+        let samples = shares.into_iter().map(|(i, share)| (i, &share.0));
+        let g2 = interpolate(self.threshold() + 1, samples)?;
+        Ok(prf_output(input.as_ref(), g2, len))
+    }
+}
+
+/// Returns the hash of the PRF input in `G2`.
+fn hash_prf_input<M: AsRef<[u8]>>(input: M) -> G2 {
+    hash_to_g2(input, INPUT_DST)
+}
+
+/// Returns `len` bytes of output, computed from the input and the PRF's group element.
+///
+/// The first block is the hash of the input and group element; the output consists of the hashes
+/// of the first block and a counter.
+fn prf_output(input: &[u8], g2: G2, len: usize) -> Vec<u8> {
+    let mut msg = OUTPUT_DST.to_vec();
+    let mut input_len = [0; 8];
+    BigEndian::write_u64(&mut input_len, input.len() as u64);
+    msg.extend(&input_len);
+    msg.extend(input);
+    msg.extend(g2.into_affine().into_compressed().as_ref());
+    let seed = sha3_256(&msg);
+    let mut output = Vec::with_capacity(len + 32);
+    let mut block_input = [0; 40];
+    block_input[..32].copy_from_slice(&seed);
+    let mut counter = 0;
+    while output.len() < len {
+        BigEndian::write_u64(&mut block_input[32..], counter);
+        output.extend(&sha3_256(&block_input));
+        counter += 1;
+    }
+    output.truncate(len);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rand::{self, random};
+
+    use super::super::SecretKeySet;
+    use super::*;
+
+    #[test]
+    fn test_simple_prf() {
+        let sk0: SecretKey = random();
+        let sk1: SecretKey = random();
+        assert_eq!(sk0.prf("object 1", 32), sk0.prf("object 1", 32));
+        assert_ne!(sk0.prf("object 1", 32), sk0.prf("object 2", 32));
+        assert_ne!(sk0.prf("object 1", 32), sk1.prf("object 1", 32));
+        // Shorter outputs are prefixes of longer ones.
+        assert_eq!(100, sk0.prf("object 1", 100).len());
+        assert_eq!(sk0.prf("object 1", 16)[..], sk0.prf("object 1", 100)[..16]);
+    }
+
+    #[test]
+    fn test_threshold_prf() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng).expect("Failed to create `SecretKeySet`");
+        let pk_set = sk_set.public_keys();
+        let input = b"bucket/object-17";
+
+        let prf_shares = |indices: &[usize]| -> BTreeMap<_, _> {
+            indices
+                .iter()
+                .map(|&i| {
+                    let sk_share = sk_set
+                        .secret_key_share(i)
+                        .unwrap_or_else(|_| panic!("Failed to create `SecretKeyShare` #{}", i));
+                    (i, sk_share.prf_share(input))
+                })
+                .collect()
+        };
+
+        // Each partial evaluation can be verified.
+        let shares = prf_shares(&[0, 3, 5]);
+        for (i, share) in &shares {
+            assert!(pk_set.public_key_share(*i).verify_prf_share(input, share));
+            assert!(!pk_set
+                .public_key_share(*i + 1)
+                .verify_prf_share(input, share));
+            assert!(!pk_set
+                .public_key_share(*i)
+                .verify_prf_share(b"other", share));
+        }
+
+        // Any `threshold + 1` shares yield the value of the PRF with the master key.
+        let output = pk_set
+            .combine_prf_shares(input, &shares, 32)
+            .expect("shares match");
+        let output2 = pk_set
+            .combine_prf_shares(input, &prf_shares(&[1, 2, 4]), 32)
+            .expect("shares match");
+        assert_eq!(output, output2);
+        let sk_master = sk_set
+            .secret_key()
+            .expect("Failed to create master `SecretKey`");
+        assert_eq!(output, sk_master.prf(input, 32));
+
+        // Fewer shares don't suffice.
+        assert!(pk_set
+            .combine_prf_shares(input, &prf_shares(&[1, 2]), 32)
+            .is_err());
+    }
+
+    #[test]
+    fn test_prf_share_is_not_a_signature() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng).expect("Failed to create `SecretKeySet`");
+        let sk_share = sk_set.secret_key_share(0).expect("Failed to create share");
+        let input = b"bucket/object-17";
+        let share = sk_share.prf_share(input);
+
+        // Signing the input, with or without the domain separation tag, doesn't reveal the share.
+        let mut msg = INPUT_DST.to_vec();
+        msg.extend(&input[..]);
+        assert_ne!(share.0, (sk_share.sign(&msg).0).0);
+        assert_ne!(share.0, (sk_share.sign(input).0).0);
+    }
+}
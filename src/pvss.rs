@@ -0,0 +1,286 @@
+//! Publicly verifiable secret sharing, following SCRAPE.
+//!
+//! The dealer shares a secret `s * g1`, where `s` is the value at `0` of a random polynomial `p`
+//! of degree `threshold`. Participant `i`, with public key `pk_i = sk_i * g1`, receives the share
+//! `p(i + 1)` encrypted as `p(i + 1) * pk_i`. Along with the encrypted shares, the dealer publishes
+//! `p(i + 1) * g2` for each participant, and a `Commitment` to `p` with respect to the independent
+//! generator `h` of `pedersen::blinding_generator`. Unlike a `Commitment` with respect to `g1`,
+//! this doesn't reveal the secret `s * g1`.
+//!
+//! Anyone can verify the dealing using only the participants' public keys:
+//!
+//! * The values `p(i + 1) * g2` lie on a polynomial of degree `threshold`: This is checked using a
+//!   random codeword of the dual code of the Reed-Solomon code, which must be orthogonal to them.
+//! * They match the commitment, which is checked with a pairing for a random linear combination.
+//! * Each encrypted share matches: `e(p(i + 1) * pk_i, g2) == e(pk_i, p(i + 1) * g2)`.
+//!
+//! The random coefficients are derived from the hash of the dealing, so the verification is
+//! deterministic. To reconstruct the secret, each participant decrypts their share to
+//! `p(i + 1) * g1`, which can again be verified by anyone with a pairing, and any `threshold + 1`
+//! decrypted shares are interpolated.
+
+use pairing::bls12_381::{Bls12, Fr, G1Affine, G2Affine, G1, G2};
+use pairing::{CurveAffine, CurveProjective, Engine, Field};
+use rand::Rng;
+
+use super::error::Result;
+use super::pedersen::blinding_generator;
+use super::poly::{Commitment, Poly};
+use super::{hash_rng, interpolate, serde_impl, IntoFr, PublicKey, SecretKey};
+
+/// The domain separation tag for deriving the verification's random coefficients.
+const VERIFY_DST: &[u8] = b"threshold_crypto PVSS verification";
+
+/// A dealing: the encrypted shares of a secret, with the information needed to verify them.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct PvssDealing {
+    /// The commitment to the polynomial, with respect to the generator `h`.
+    commit: Commitment,
+    /// The encrypted shares `p(i + 1) * pk_i`.
+    #[serde(with = "serde_impl::projective_vec")]
+    encrypted_shares: Vec<G1>,
+    /// The values `p(i + 1) * g2`.
+    #[serde(with = "serde_impl::projective_vec")]
+    share_commits: Vec<G2>,
+}
+
+/// A decrypted share `p(i + 1) * g1`.
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DecryptedShare(#[serde(with = "serde_impl::projective")] G1);
+
+impl PvssDealing {
+    /// Creates a dealing of a random secret, for the participants with the given public keys.
+    /// Participant `i` receives share number `i`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn new<R: Rng>(pub_keys: &[PublicKey], threshold: usize, rng: &mut R) -> Result<Self> {
+        let poly = Poly::random(threshold, rng)?;
+        Ok(PvssDealing::from_poly(&poly, pub_keys))
+    }
+
+    /// Creates a dealing of the secret `poly(0) * g1`, for the participants with the given public
+    /// keys.
+    pub fn from_poly(poly: &Poly, pub_keys: &[PublicKey]) -> Self {
+        let h = blinding_generator().into_affine();
+        let commit = Commitment {
+            coeff: poly.coeff.iter().map(|c| h.mul(*c)).collect(),
+        };
+        let (encrypted_shares, share_commits) = pub_keys
+            .iter()
+            .enumerate()
+            .map(|(i, pk)| {
+                let value = poly.evaluate(i + 1);
+                (pk.0.into_affine().mul(value), G2Affine::one().mul(value))
+            })
+            .unzip();
+        PvssDealing {
+            commit,
+            encrypted_shares,
+            share_commits,
+        }
+    }
+
+    /// Returns the threshold `t`: any `t + 1` decrypted shares can reconstruct the secret.
+    pub fn threshold(&self) -> usize {
+        self.commit.degree()
+    }
+
+    /// Returns the commitment to the polynomial, with respect to `pedersen::blinding_generator`.
+    pub fn commitment(&self) -> &Commitment {
+        &self.commit
+    }
+
+    /// Returns `true` if the dealing contains valid shares of a polynomial of degree `threshold`
+    /// for the participants with the given public keys.
+    pub fn verify(&self, pub_keys: &[PublicKey], threshold: usize) -> bool {
+        let n = pub_keys.len();
+        if self.commit.coeff.len() != threshold + 1
+            || self.encrypted_shares.len() != n
+            || self.share_commits.len() != n
+            || n <= threshold
+        {
+            return false;
+        }
+        let mut rng = hash_rng(self.transcript(pub_keys));
+
+        // The dual code test: For a random polynomial `f` of degree `n - threshold - 2`, the sum of
+        // `f(x_i) * p(x_i) / prod(x_i - x_j)` over all `i`, with `j != i`, must vanish.
+        if n > threshold + 1 {
+            let f_coeff: Vec<Fr> = (0..(n - threshold - 1)).map(|_| rng.gen()).collect();
+            let f = Poly::new(f_coeff).expect("the dual codeword is not secret");
+            let mut sum = G2::zero();
+            for (i, v) in self.share_commits.iter().enumerate() {
+                let mut c = f.evaluate(i + 1);
+                c.mul_assign(&lagrange_denom_inverse(i, n));
+                sum.add_assign(&v.into_affine().mul(c));
+            }
+            if !sum.is_zero() {
+                return false;
+            }
+        }
+
+        // The values must match the commitment.
+        let mut commit_sum = G1::zero();
+        let mut v_sum = G2::zero();
+        for (i, v) in self.share_commits.iter().enumerate() {
+            let lambda: Fr = rng.gen();
+            let value = self.commit.evaluate(i + 1);
+            commit_sum.add_assign(&value.into_affine().mul(lambda));
+            v_sum.add_assign(&v.into_affine().mul(lambda));
+        }
+        if Bls12::pairing(commit_sum, G2Affine::one())
+            != Bls12::pairing(blinding_generator(), v_sum)
+        {
+            return false;
+        }
+
+        // The encrypted shares must match the values.
+        pub_keys
+            .iter()
+            .zip(&self.encrypted_shares)
+            .zip(&self.share_commits)
+            .all(|((pk, enc), v)| Bls12::pairing(*enc, G2Affine::one()) == Bls12::pairing(pk.0, *v))
+    }
+
+    /// Returns `true` if the decrypted share is the `i`-th share of this dealing.
+    pub fn verify_decrypted_share(&self, i: usize, share: &DecryptedShare) -> bool {
+        match self.share_commits.get(i) {
+            Some(v) => {
+                Bls12::pairing(share.0, G2Affine::one()) == Bls12::pairing(G1Affine::one(), *v)
+            }
+            None => false,
+        }
+    }
+
+    /// Combines the decrypted shares into the secret `s * g1`.
+    ///
+    /// The shares are not verified: Use `verify_decrypted_share` to check them first.
+    pub fn reconstruct<'a, T, I>(&self, shares: I) -> Result<G1>
+    where
+        I: IntoIterator<Item = (T, &'a DecryptedShare)>,
+        T: IntoFr,
+    {
+        let samples = shares.into_iter().map(|(i, share)| (i, &share.0));
+        interpolate(self.threshold() + 1, samples)
+    }
+
+    /// Returns the serialized dealing and public keys, from which the verification's random
+    /// coefficients are derived.
+    fn transcript(&self, pub_keys: &[PublicKey]) -> Vec<u8> {
+        let mut msg = VERIFY_DST.to_vec();
+        let g1s = self
+            .commit
+            .coeff
+            .iter()
+            .chain(&self.encrypted_shares)
+            .chain(pub_keys.iter().map(|pk| &pk.0));
+        for g1 in g1s {
+            msg.extend(g1.into_affine().into_compressed().as_ref());
+        }
+        for g2 in &self.share_commits {
+            msg.extend(g2.into_affine().into_compressed().as_ref());
+        }
+        msg
+    }
+}
+
+impl SecretKey {
+    /// Decrypts the `i`-th share of the dealing, or returns `None` if there is no such share.
+    ///
+    /// This key must belong to the `i`-th participant.
+    pub fn decrypt_pvss_share(&self, dealing: &PvssDealing, i: usize) -> Option<DecryptedShare> {
+        let enc = dealing.encrypted_shares.get(i)?;
+        let sk_inv = self.0.inverse()?;
+        Some(DecryptedShare(enc.into_affine().mul(sk_inv)))
+    }
+}
+
+/// Returns `1 / prod(x_i - x_j)`, where `j` ranges over all indices in `0..n` except `i`, and
+/// `x_i = i + 1`.
+fn lagrange_denom_inverse(i: usize, n: usize) -> Fr {
+    let x_i = (i as u64 + 1).into_fr();
+    let mut denom = Fr::one();
+    for j in (0..n).filter(|&j| j != i) {
+        let mut diff = x_i;
+        diff.sub_assign(&(j as u64 + 1).into_fr());
+        denom.mul_assign(&diff);
+    }
+    denom.inverse().expect("indices are different")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rand::{self, random};
+
+    use super::*;
+
+    #[test]
+    fn test_pvss() {
+        let mut rng = rand::thread_rng();
+        let sec_keys: Vec<SecretKey> = (0..5).map(|_| random()).collect();
+        let pub_keys: Vec<_> = sec_keys.iter().map(SecretKey::public_key).collect();
+        let poly = Poly::random(2, &mut rng).expect("Failed to create polynomial");
+        let dealing = PvssDealing::from_poly(&poly, &pub_keys);
+
+        // An observer verifies the dealing with only the public keys.
+        assert!(dealing.verify(&pub_keys, 2));
+        assert!(!dealing.verify(&pub_keys, 1));
+        assert!(!dealing.verify(&pub_keys[1..], 2));
+        let mut swapped = pub_keys.clone();
+        swapped.swap(1, 2);
+        assert!(!dealing.verify(&swapped, 2));
+
+        // Participants decrypt their shares, which anyone can verify.
+        let shares: BTreeMap<_, _> = sec_keys
+            .iter()
+            .enumerate()
+            .map(|(i, sk)| {
+                let share = sk.decrypt_pvss_share(&dealing, i).expect("share exists");
+                assert!(dealing.verify_decrypted_share(i, &share));
+                assert!(!dealing.verify_decrypted_share(i + 1, &share));
+                (i, share)
+            })
+            .collect();
+
+        // Any `threshold + 1` shares reconstruct the secret.
+        let secret = G1Affine::one().mul(poly.evaluate(0));
+        let secret0 = dealing
+            .reconstruct(shares.iter().take(3).map(|(i, s)| (*i, s)))
+            .expect("Failed to reconstruct");
+        let secret1 = dealing
+            .reconstruct(shares.iter().skip(2).map(|(i, s)| (*i, s)))
+            .expect("Failed to reconstruct");
+        assert_eq!(secret, secret0);
+        assert_eq!(secret, secret1);
+    }
+
+    #[test]
+    fn test_invalid_dealing() {
+        let mut rng = rand::thread_rng();
+        let pub_keys: Vec<_> = (0..4).map(|_| random::<SecretKey>().public_key()).collect();
+        let dealing = PvssDealing::new(&pub_keys, 1, &mut rng).expect("Failed to deal");
+        assert!(dealing.verify(&pub_keys, 1));
+
+        // An encrypted share that doesn't match is detected.
+        let mut tampered = dealing.clone();
+        tampered.encrypted_shares[2].add_assign(&G1::one());
+        assert!(!tampered.verify(&pub_keys, 1));
+
+        // Values that are not on a polynomial of degree `threshold` are detected, even if the
+        // encrypted shares match them.
+        let mut tampered = dealing.clone();
+        let r: Fr = rng.gen();
+        tampered.share_commits[3] = G2Affine::one().mul(r);
+        tampered.encrypted_shares[3] = pub_keys[3].0.into_affine().mul(r);
+        assert!(!tampered.verify(&pub_keys, 1));
+
+        // A commitment to a different polynomial is detected.
+        let mut tampered = dealing;
+        tampered.commit.coeff[1].add_assign(&blinding_generator());
+        assert!(!tampered.verify(&pub_keys, 1));
+    }
+}
@@ -0,0 +1,295 @@
+//! Proactive refresh of secret key shares.
+//!
+//! An adversary who compromises different share holders over a long period of time could
+//! eventually collect `threshold + 1` shares. To prevent that, the holders periodically refresh
+//! their shares: Each holder deals a random polynomial with a zero constant term, and every holder
+//! adds the values of all these polynomials at their index to their secret key share. Since the
+//! sum of the polynomials vanishes at `0`, the master key stays the same, so the `PublicKeySet`'s
+//! `public_key()`, and all signatures and ciphertexts made with it, remain valid. Old shares,
+//! however, can't be combined with refreshed ones anymore.
+//!
+//! Each refresh is published as a `Commitment` whose constant term is zero, which allows every
+//! holder to verify their part of it. The holders' parts are sent to them encrypted to their
+//! public keys.
+
+use pairing::bls12_381::Fr;
+use pairing::{CurveProjective, Field};
+use rand::Rng;
+
+use super::error::{Error, Result};
+use super::poly::{Commitment, Poly};
+use super::{
+    into_fr_plus_1, Ciphertext, IntoFr, PublicKey, PublicKeySet, SecretKey, SecretKeyShare,
+};
+
+/// A random polynomial with a zero constant term, dealt by one share holder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Refresh {
+    poly: Poly,
+}
+
+impl Refresh {
+    /// Creates a random refresh for key sets with the given threshold.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn random<R: Rng>(threshold: usize, rng: &mut R) -> Result<Self> {
+        let mut poly = Poly::random(threshold, rng)?;
+        poly.coeff[0] = Fr::zero();
+        Ok(Refresh { poly })
+    }
+
+    /// Returns the threshold `t` of the key sets this refresh applies to.
+    pub fn threshold(&self) -> usize {
+        self.poly.degree()
+    }
+
+    /// Returns the commitment, which needs to be published to all share holders.
+    pub fn commitment(&self) -> Commitment {
+        self.poly.commitment()
+    }
+
+    /// Returns the refresh for the `i`-th secret key share, which needs to be sent to its holder,
+    /// encrypted with `RefreshShare::encrypt`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn share<T: IntoFr>(&self, i: T) -> Result<RefreshShare> {
+        let mut fr = self.poly.evaluate(into_fr_plus_1(i));
+        SecretKey::from_mut_ptr(&mut fr as *mut Fr).map(RefreshShare)
+    }
+}
+
+/// The refresh for a single secret key share.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefreshShare(SecretKey);
+
+impl RefreshShare {
+    /// Decrypts the `i`-th share of the refresh with the given commitment, with the `i`-th share
+    /// holder's secret key, and verifies it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidShare` if the ciphertext can't be decrypted or the share doesn't
+    /// match the commitment, or an `Error::MlockFailed` if we have reached the systems's locked
+    /// memory limit.
+    pub fn decrypt<T: IntoFr>(
+        i: T,
+        commit: &Commitment,
+        sk: &SecretKey,
+        ct: &Ciphertext,
+    ) -> Result<Self> {
+        let share = RefreshShare(sk.decrypt_secret_key(ct)?);
+        if !share.verify(i, commit) {
+            return Err(Error::InvalidShare);
+        }
+        Ok(share)
+    }
+
+    /// Returns `true` if this is the `i`-th share of the refresh with the given commitment.
+    pub fn verify<T: IntoFr>(&self, i: T, commit: &Commitment) -> bool {
+        self.0.public_key().0 == commit.evaluate(into_fr_plus_1(i))
+    }
+
+    /// Returns the share encrypted to its holder's public key, so that it can be sent to them.
+    pub fn encrypt(&self, pk: &PublicKey) -> Ciphertext {
+        self.0.encrypt_to(pk)
+    }
+}
+
+impl PublicKeySet {
+    /// Returns `true` if the commitment belongs to a valid refresh for this key set, i.e. it has
+    /// a zero constant term and at most this key set's degree.
+    pub fn verify_refresh(&self, commit: &Commitment) -> bool {
+        commit.coeff.len() <= self.commit.coeff.len()
+            && commit.coeff.first().filter(|c| c.is_zero()).is_some()
+    }
+
+    /// Returns the refreshed key set, after applying the refreshes with the given commitments.
+    /// The public key remains the same.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidShare` if any of the commitments is not a valid refresh.
+    pub fn refresh<'a, I>(&self, commits: I) -> Result<PublicKeySet>
+    where
+        I: IntoIterator<Item = &'a Commitment>,
+    {
+        let mut result = self.commit.clone();
+        for commit in commits {
+            if !self.verify_refresh(commit) {
+                return Err(Error::InvalidShare);
+            }
+            result += commit;
+        }
+        Ok(PublicKeySet::from(result))
+    }
+}
+
+impl SecretKeyShare {
+    /// Returns the refreshed `i`-th secret key share of the key set with the given public keys,
+    /// after applying the given refreshes. Its public key share is the `i`-th share of the
+    /// refreshed `PublicKeySet`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidShare` if any refresh is invalid or doesn't match its commitment,
+    /// or an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn refresh<'a, T, I>(&self, i: T, pk_set: &PublicKeySet, refreshes: I) -> Result<Self>
+    where
+        T: IntoFr,
+        I: IntoIterator<Item = (&'a Commitment, &'a RefreshShare)>,
+    {
+        let refreshes: Vec<_> = refreshes.into_iter().collect();
+        let is_valid = |&(commit, share): &(&Commitment, &RefreshShare)| {
+            pk_set.verify_refresh(commit) && share.verify(i, commit)
+        };
+        if !refreshes.iter().all(is_valid) {
+            return Err(Error::InvalidShare);
+        }
+        let mut fr = *(self.0).0;
+        for (_, share) in refreshes {
+            fr.add_assign(&(share.0).0);
+        }
+        SecretKeyShare::from_mut_ptr(&mut fr as *mut Fr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use bincode;
+    use rand;
+
+    use super::super::SecretKeySet;
+    use super::*;
+
+    #[test]
+    fn test_refresh() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng).expect("Failed to create key set");
+        let pk_set = sk_set.public_keys();
+        let msg = "Signed before the refresh";
+        let ct = pk_set.public_key().encrypt(msg);
+
+        // Every holder deals a refresh.
+        let refreshes: Vec<_> = (0..5)
+            .map(|_| Refresh::random(2, &mut rng).expect("Failed to create refresh"))
+            .collect();
+        let commits: Vec<_> = refreshes.iter().map(Refresh::commitment).collect();
+        let new_pk_set = pk_set.refresh(&commits).expect("Failed to refresh");
+        assert_eq!(pk_set.public_key(), new_pk_set.public_key());
+        assert_ne!(pk_set.public_key_share(0), new_pk_set.public_key_share(0));
+
+        // Every holder refreshes their share.
+        let old_shares: Vec<_> = (0..5)
+            .map(|i| sk_set.secret_key_share(i).expect("Failed to create share"))
+            .collect();
+        let new_shares: Vec<_> = old_shares
+            .iter()
+            .enumerate()
+            .map(|(i, old)| {
+                let shares: Vec<_> = refreshes
+                    .iter()
+                    .map(|r| r.share(i).expect("Failed to create refresh share"))
+                    .collect();
+                let new = old
+                    .refresh(i, &pk_set, commits.iter().zip(&shares))
+                    .expect("Failed to refresh share");
+                assert_eq!(new_pk_set.public_key_share(i), new.public_key_share());
+                new
+            })
+            .collect();
+
+        // The new shares decrypt the old ciphertext, and sign for the same public key.
+        let dec_shares: BTreeMap<_, _> = [0, 2, 4]
+            .iter()
+            .map(|&i| {
+                (
+                    i,
+                    new_shares[i]
+                        .decrypt_share(&ct)
+                        .expect("ciphertext is valid"),
+                )
+            })
+            .collect();
+        let decrypted = new_pk_set
+            .decrypt(&dec_shares, &ct)
+            .expect("Failed to decrypt");
+        assert_eq!(msg.as_bytes(), &decrypted[..]);
+        let sig_shares: BTreeMap<_, _> = [1, 2, 3]
+            .iter()
+            .map(|&i| (i, new_shares[i].sign(msg)))
+            .collect();
+        let sig = new_pk_set
+            .combine_signatures(&sig_shares)
+            .expect("Failed to combine signatures");
+        assert!(pk_set.public_key().verify(&sig, msg));
+
+        // Old and new shares can't be combined anymore.
+        let mixed: BTreeMap<_, _> = vec![
+            (0, old_shares[0].sign(msg)),
+            (1, new_shares[1].sign(msg)),
+            (2, new_shares[2].sign(msg)),
+        ]
+        .into_iter()
+        .collect();
+        let sig = new_pk_set
+            .combine_signatures(&mixed)
+            .expect("Failed to combine signatures");
+        assert!(!pk_set.public_key().verify(&sig, msg));
+    }
+
+    #[test]
+    fn test_invalid_refresh() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng).expect("Failed to create key set");
+        let pk_set = sk_set.public_keys();
+        let share = sk_set.secret_key_share(3).expect("Failed to create share");
+
+        // A polynomial with a nonzero constant term would change the public key.
+        let poly = Poly::random(1, &mut rng).expect("Failed to create polynomial");
+        let commit = poly.commitment();
+        assert!(!pk_set.verify_refresh(&commit));
+        assert_eq!(Err(Error::InvalidShare), pk_set.refresh(Some(&commit)));
+
+        // A refresh of a higher degree would change the threshold.
+        let refresh = Refresh::random(2, &mut rng).expect("Failed to create refresh");
+        assert!(!pk_set.verify_refresh(&refresh.commitment()));
+
+        // A refresh share that doesn't match the commitment is rejected.
+        let refresh = Refresh::random(1, &mut rng).expect("Failed to create refresh");
+        let commit = refresh.commitment();
+        let wrong = refresh.share(2).expect("Failed to create refresh share");
+        assert!(!wrong.verify(3, &commit));
+        assert!(share.refresh(3, &pk_set, Some((&commit, &wrong))).is_err());
+        let right = refresh.share(3).expect("Failed to create refresh share");
+        assert!(share.refresh(3, &pk_set, Some((&commit, &right))).is_ok());
+    }
+
+    #[test]
+    fn test_encrypted_refresh_share() {
+        let mut rng = rand::thread_rng();
+        let refresh = Refresh::random(1, &mut rng).expect("Failed to create refresh");
+        let commit = refresh.commitment();
+        let holder_sk = SecretKey::random();
+        let share = refresh.share(3).expect("Failed to create refresh share");
+
+        // The share is sent to its holder, encrypted to their public key, and verified on receipt.
+        let ser_ct = bincode::serialize(&share.encrypt(&holder_sk.public_key()))
+            .expect("serialize ciphertext");
+        let ct: Ciphertext = bincode::deserialize(&ser_ct).expect("deserialize ciphertext");
+        let received = RefreshShare::decrypt(3, &commit, &holder_sk, &ct).expect("valid share");
+        assert_eq!(share, received);
+
+        // It can't be decrypted with another key, or accepted as another holder's share.
+        let other_sk = SecretKey::random();
+        let result = RefreshShare::decrypt(3, &commit, &other_sk, &ct);
+        assert_eq!(Err(Error::InvalidShare), result);
+        let result = RefreshShare::decrypt(2, &commit, &holder_sk, &ct);
+        assert_eq!(Err(Error::InvalidShare), result);
+    }
+}
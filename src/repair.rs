@@ -0,0 +1,329 @@
+//! Repairing a lost secret key share, or enrolling a new holder at an unused index.
+//!
+//! A set of `threshold + 1` helpers can compute the secret key share at any index `r`: It is the
+//! sum of their shares, each multiplied by the value at `r` of the Lagrange polynomial for the
+//! helpers' indices. To avoid revealing their own shares, the protocol takes two rounds:
+//!
+//! 1. Each helper computes their weighted share, and splits it into random masks, one for each
+//!    helper, that add up to it. They send one mask to each helper.
+//! 2. Each helper adds up the masks they received, and sends the sum to the target.
+//!
+//! The target adds up the sums and verifies the result against `public_key_share(r)`. Each sum it
+//! receives is uniformly random on its own, and the helpers only learn random masks from each
+//! other.
+//!
+//! The masks and sums are sent encrypted to their recipients' public keys. Each helper also
+//! publishes the public keys of their masks, which anyone can check against the `PublicKeySet`
+//! with `verify_repair_masks`. Every recipient then verifies a mask, or a sum of masks, against
+//! the corresponding public key, or sum of public keys, as soon as they decrypt it.
+
+use pairing::bls12_381::Fr;
+use pairing::Field;
+use rand::Rng;
+
+use super::error::{Error, Result};
+use super::{
+    into_fr_plus_1, lagrange_coefficients, Ciphertext, IntoFr, PublicKey, PublicKeySet, SecretKey,
+    SecretKeyShare,
+};
+
+/// A random mask, or a sum of masks, exchanged during a repair.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepairMask(SecretKey);
+
+impl RepairMask {
+    /// Returns the sum of the masks a helper received in the first round, which needs to be sent
+    /// to the target.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn sum<'a, I>(masks: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = &'a RepairMask>,
+    {
+        let mut fr = Fr::zero();
+        for mask in masks {
+            fr.add_assign(&(mask.0).0);
+        }
+        SecretKey::from_mut_ptr(&mut fr as *mut Fr).map(RepairMask)
+    }
+
+    /// Decrypts a mask, or a sum of masks, with the recipient's secret key, and verifies it
+    /// against its public key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidShare` if the ciphertext can't be decrypted or the mask doesn't
+    /// match the public key, or an `Error::MlockFailed` if we have reached the systems's locked
+    /// memory limit.
+    pub fn decrypt(mask_pk: &PublicKey, sk: &SecretKey, ct: &Ciphertext) -> Result<Self> {
+        let mask = RepairMask(sk.decrypt_secret_key(ct)?);
+        if mask.public_key() != *mask_pk {
+            return Err(Error::InvalidShare);
+        }
+        Ok(mask)
+    }
+
+    /// Returns the mask's public key, which is published so that the recipient can verify it.
+    pub fn public_key(&self) -> PublicKey {
+        self.0.public_key()
+    }
+
+    /// Returns the mask encrypted to the recipient's public key, so that it can be sent to them.
+    pub fn encrypt(&self, pk: &PublicKey) -> Ciphertext {
+        self.0.encrypt_to(pk)
+    }
+}
+
+impl PublicKeySet {
+    /// Returns `true` if the public keys of the masks by the helper with the `i`-th share, in the
+    /// order of `helpers`, belong to a valid split of their weighted share for repairing the
+    /// share at index `target`.
+    pub fn verify_repair_masks<T, U>(
+        &self,
+        i: T,
+        helpers: &[T],
+        target: U,
+        mask_pks: &[PublicKey],
+    ) -> bool
+    where
+        T: IntoFr,
+        U: IntoFr,
+    {
+        let l = match repair_coefficient(i, helpers, target) {
+            Ok(l) => l,
+            Err(_) => return false,
+        };
+        if mask_pks.len() != helpers.len() {
+            return false;
+        }
+        let mut sum = mask_pks[0];
+        for mask_pk in &mask_pks[1..] {
+            sum += mask_pk;
+        }
+        sum == self.public_key_share(i).0 * l
+    }
+}
+
+impl SecretKeyShare {
+    /// Returns the masks that the helper with this `i`-th share needs to send to the helpers,
+    /// in the order of their indices in `helpers`, to repair the share at index `target`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidShare` if `i` is not among the helpers, an
+    /// `Error::DuplicateEntry` if an index appears twice, or an `Error::MlockFailed` if we have
+    /// reached the systems's locked memory limit.
+    pub fn repair_masks<T, U, R>(
+        &self,
+        i: T,
+        helpers: &[T],
+        target: U,
+        rng: &mut R,
+    ) -> Result<Vec<RepairMask>>
+    where
+        T: IntoFr,
+        U: IntoFr,
+        R: Rng,
+    {
+        let l = repair_coefficient(i, helpers, target)?;
+        let mut rest = *(self.0).0;
+        rest.mul_assign(&l);
+        let mut masks = Vec::with_capacity(helpers.len());
+        for _ in 1..helpers.len() {
+            let mut fr: Fr = rng.gen();
+            rest.sub_assign(&fr);
+            masks.push(RepairMask(SecretKey::from_mut_ptr(&mut fr as *mut Fr)?));
+        }
+        masks.push(RepairMask(SecretKey::from_mut_ptr(&mut rest as *mut Fr)?));
+        Ok(masks)
+    }
+
+    /// Returns the repaired share with the given index, given the sums of masks sent by all the
+    /// helpers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidShare` if the result doesn't match the `PublicKeySet`'s public key
+    /// share, or an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn from_repair<'a, T, I>(index: T, pk_set: &PublicKeySet, sums: I) -> Result<Self>
+    where
+        T: IntoFr,
+        I: IntoIterator<Item = &'a RepairMask>,
+    {
+        let RepairMask(sum) = RepairMask::sum(sums)?;
+        let share = SecretKeyShare(sum);
+        if share.public_key_share() != pk_set.public_key_share(index) {
+            return Err(Error::InvalidShare);
+        }
+        Ok(share)
+    }
+}
+
+/// Returns the factor by which the helper with the `i`-th share multiplies it, to repair the share
+/// at index `target`: the value at `target` of the Lagrange polynomial for `i` and the helpers.
+///
+/// # Errors
+///
+/// Returns an `Error::InvalidShare` if `i` is not among the helpers, or an
+/// `Error::DuplicateEntry` if an index appears twice.
+fn repair_coefficient<T: IntoFr, U: IntoFr>(i: T, helpers: &[T], target: U) -> Result<Fr> {
+    let x = into_fr_plus_1(i);
+    let target_x = into_fr_plus_1(target);
+    // The Lagrange coefficients at `target_x` are the ones at `0` for the shifted points.
+    let xs: Vec<Fr> = helpers
+        .iter()
+        .map(|&h| {
+            let mut shifted = into_fr_plus_1(h);
+            shifted.sub_assign(&target_x);
+            shifted
+        })
+        .collect();
+    let pos = helpers
+        .iter()
+        .position(|&h| into_fr_plus_1(h) == x)
+        .ok_or(Error::InvalidShare)?;
+    Ok(lagrange_coefficients(&xs)?[pos])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use bincode;
+    use rand;
+
+    use super::super::SecretKeySet;
+    use super::*;
+
+    /// Returns the ciphertext after sending it over the wire.
+    fn send(ct: &Ciphertext) -> Ciphertext {
+        let msg = bincode::serialize(ct).expect("serialize ciphertext");
+        bincode::deserialize(&msg).expect("deserialize ciphertext")
+    }
+
+    /// Runs the repair protocol for the `target` index, with the given helpers, and returns the
+    /// sums received and verified by the target.
+    fn repair(sk_set: &SecretKeySet, helpers: &[usize], target: usize) -> Vec<RepairMask> {
+        let mut rng = rand::thread_rng();
+        let pk_set = sk_set.public_keys();
+        let helper_sks: Vec<SecretKey> = helpers.iter().map(|_| SecretKey::random()).collect();
+        let target_sk = SecretKey::random();
+
+        // Each helper publishes the public keys of their masks, and sends the masks encrypted.
+        let masks: Vec<Vec<RepairMask>> = helpers
+            .iter()
+            .map(|&i| {
+                let share = sk_set.secret_key_share(i).expect("Failed to create share");
+                share
+                    .repair_masks(i, helpers, target, &mut rng)
+                    .expect("Failed to create masks")
+            })
+            .collect();
+        let mask_pks: Vec<Vec<PublicKey>> = masks
+            .iter()
+            .map(|m| m.iter().map(RepairMask::public_key).collect())
+            .collect();
+        for (&i, pks) in helpers.iter().zip(&mask_pks) {
+            assert!(pk_set.verify_repair_masks(i, helpers, target, pks));
+        }
+
+        // Each helper verifies the masks they receive, and sends their sum to the target.
+        (0..helpers.len())
+            .map(|k| {
+                let sk = &helper_sks[k];
+                let received: Vec<_> = masks
+                    .iter()
+                    .zip(&mask_pks)
+                    .map(|(m, pks)| {
+                        let ct = send(&m[k].encrypt(&sk.public_key()));
+                        RepairMask::decrypt(&pks[k], sk, &ct).expect("valid mask")
+                    })
+                    .collect();
+                let sum = RepairMask::sum(&received).expect("Failed to sum masks");
+                let mut sum_pk = mask_pks[0][k];
+                for pks in &mask_pks[1..] {
+                    sum_pk += pks[k];
+                }
+                let ct = send(&sum.encrypt(&target_sk.public_key()));
+                RepairMask::decrypt(&sum_pk, &target_sk, &ct).expect("valid sum")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_repair() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng).expect("Failed to create key set");
+        let pk_set = sk_set.public_keys();
+
+        // Share 1 is lost and repaired by the holders of shares 0, 3 and 4.
+        let sums = repair(&sk_set, &[0, 3, 4], 1);
+        let repaired = SecretKeyShare::from_repair(1, &pk_set, &sums).expect("Failed to repair");
+        assert_eq!(sk_set.secret_key_share(1), Ok(repaired.clone()));
+
+        // A new member is enrolled at index 7.
+        let sums = repair(&sk_set, &[1, 2, 4], 7);
+        let enrolled = SecretKeyShare::from_repair(7, &pk_set, &sums).expect("Failed to repair");
+        assert_eq!(pk_set.public_key_share(7), enrolled.public_key_share());
+
+        // Their shares sign together with an existing one.
+        let msg = "Signed by repaired shares";
+        let sig_shares: BTreeMap<_, _> = vec![
+            (1, repaired.sign(msg)),
+            (7, enrolled.sign(msg)),
+            (
+                0,
+                sk_set.secret_key_share(0).expect("share exists").sign(msg),
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let sig = pk_set
+            .combine_signatures(&sig_shares)
+            .expect("Failed to combine signatures");
+        assert!(pk_set.public_key().verify(&sig, msg));
+    }
+
+    #[test]
+    fn test_invalid_repair() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng).expect("Failed to create key set");
+        let pk_set = sk_set.public_keys();
+        let share = sk_set.secret_key_share(0).expect("Failed to create share");
+
+        // A helper must be among the helpers.
+        let result = share.repair_masks(0, &[1, 2, 3], 5, &mut rng);
+        assert_eq!(Err(Error::InvalidShare), result);
+
+        // Masks that don't add up to the helper's weighted share are detected, and a mask that
+        // doesn't match its public key is rejected on receipt.
+        let masks = share
+            .repair_masks(0, &[0, 1, 2], 5, &mut rng)
+            .expect("Failed to create masks");
+        let mut mask_pks: Vec<_> = masks.iter().map(RepairMask::public_key).collect();
+        assert!(pk_set.verify_repair_masks(0, &[0, 1, 2], 5, &mask_pks));
+        assert!(!pk_set.verify_repair_masks(0, &[0, 1, 2], 6, &mask_pks));
+        assert!(!pk_set.verify_repair_masks(0, &[0, 1, 2], 5, &mask_pks[1..]));
+        mask_pks.swap(0, 1);
+        let sk = SecretKey::random();
+        let ct = masks[0].encrypt(&sk.public_key());
+        let result = RepairMask::decrypt(&mask_pks[0], &sk, &ct);
+        assert_eq!(Err(Error::InvalidShare), result);
+        let result = RepairMask::decrypt(&mask_pks[1], &SecretKey::random(), &ct);
+        assert_eq!(Err(Error::InvalidShare), result);
+        assert_eq!(
+            Ok(masks[0].clone()),
+            RepairMask::decrypt(&mask_pks[1], &sk, &ct)
+        );
+
+        // With too few helpers, or a missing sum, the result doesn't verify.
+        let sums = repair(&sk_set, &[0, 3], 1);
+        let result = SecretKeyShare::from_repair(1, &pk_set, &sums);
+        assert_eq!(Err(Error::InvalidShare), result);
+        let sums = repair(&sk_set, &[0, 2, 3], 1);
+        let result = SecretKeyShare::from_repair(1, &pk_set, &sums[1..]);
+        assert_eq!(Err(Error::InvalidShare), result);
+    }
+}
@@ -0,0 +1,310 @@
+//! Resharing a key to a new set of holders, with a new threshold.
+//!
+//! To hand over a key to a new committee without changing the master public key, at least
+//! `threshold + 1` of the old holders each deal a `Reshare`: a random polynomial of the new degree
+//! whose value at `0` is their own secret key share. Each new member receives one sub-share from
+//! every dealing, and combines them with the Lagrange coefficients of the dealers' indices. Since
+//! these coefficients interpolate the old shares at `0`, the new shares lie on a polynomial whose
+//! value at `0` is the old master key.
+//!
+//! The dealings' commitments are published: Anyone can check that each one starts with its
+//! dealer's public key share, and compute the new `PublicKeySet`, with the same `public_key()`.
+//! The sub-shares are sent to the new members encrypted to their public keys.
+
+use pairing::bls12_381::{Fr, G1};
+use pairing::{CurveProjective, Field};
+use rand::Rng;
+
+use super::error::{Error, Result};
+use super::poly::{Commitment, Poly};
+use super::{
+    into_fr_plus_1, lagrange_coefficients, Ciphertext, IntoFr, PublicKey, PublicKeySet, SecretKey,
+    SecretKeyShare,
+};
+
+/// A random polynomial whose value at `0` is an old holder's secret key share.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reshare {
+    poly: Poly,
+}
+
+impl Reshare {
+    /// Creates a random reshare of the given secret key share, with the new threshold.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn new<R: Rng>(share: &SecretKeyShare, threshold: usize, rng: &mut R) -> Result<Self> {
+        let mut poly = Poly::random(threshold, rng)?;
+        poly.coeff[0] = *(share.0).0;
+        Ok(Reshare { poly })
+    }
+
+    /// Returns the new threshold.
+    pub fn threshold(&self) -> usize {
+        self.poly.degree()
+    }
+
+    /// Returns the commitment, which needs to be published to all new members.
+    pub fn commitment(&self) -> Commitment {
+        self.poly.commitment()
+    }
+
+    /// Returns the sub-share for the new member with index `j`, which needs to be sent to them,
+    /// encrypted with `ReshareShare::encrypt`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn share<T: IntoFr>(&self, j: T) -> Result<ReshareShare> {
+        let mut fr = self.poly.evaluate(into_fr_plus_1(j));
+        SecretKey::from_mut_ptr(&mut fr as *mut Fr).map(ReshareShare)
+    }
+}
+
+/// A new member's sub-share of one old holder's secret key share.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReshareShare(SecretKey);
+
+impl ReshareShare {
+    /// Decrypts the `j`-th sub-share of the reshare with the given commitment, with the new
+    /// member's secret key, and verifies it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::InvalidShare` if the ciphertext can't be decrypted or the sub-share
+    /// doesn't match the commitment, or an `Error::MlockFailed` if we have reached the systems's
+    /// locked memory limit.
+    pub fn decrypt<T: IntoFr>(
+        j: T,
+        commit: &Commitment,
+        sk: &SecretKey,
+        ct: &Ciphertext,
+    ) -> Result<Self> {
+        let share = ReshareShare(sk.decrypt_secret_key(ct)?);
+        if !share.verify(j, commit) {
+            return Err(Error::InvalidShare);
+        }
+        Ok(share)
+    }
+
+    /// Returns `true` if this is the `j`-th sub-share of the reshare with the given commitment.
+    pub fn verify<T: IntoFr>(&self, j: T, commit: &Commitment) -> bool {
+        self.0.public_key().0 == commit.evaluate(into_fr_plus_1(j))
+    }
+
+    /// Returns the sub-share encrypted to the new member's public key, so that it can be sent to
+    /// them.
+    pub fn encrypt(&self, pk: &PublicKey) -> Ciphertext {
+        self.0.encrypt_to(pk)
+    }
+}
+
+impl PublicKeySet {
+    /// Returns `true` if the commitment belongs to a valid reshare of the `i`-th secret key share,
+    /// with the new threshold.
+    pub fn verify_reshare<T: IntoFr>(&self, i: T, commit: &Commitment, threshold: usize) -> bool {
+        commit.coeff.len() == threshold + 1 && commit.coeff[0] == (self.public_key_share(i).0).0
+    }
+
+    /// Returns the new key set, given the reshares' commitments by their dealers' indices. The
+    /// public key remains the same.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::NotEnoughShares` if there are fewer than `threshold + 1` reshares, an
+    /// `Error::DuplicateEntry` if an index appears twice, or an `Error::InvalidShare` if any of
+    /// the commitments is not a valid reshare with the new threshold.
+    pub fn reshare<'a, T, I>(&self, threshold: usize, commits: I) -> Result<PublicKeySet>
+    where
+        I: IntoIterator<Item = (T, &'a Commitment)>,
+        T: IntoFr,
+    {
+        let commits: Vec<_> = commits.into_iter().collect();
+        if commits.len() <= self.threshold() {
+            return Err(Error::NotEnoughShares);
+        }
+        if !commits
+            .iter()
+            .all(|&(i, commit)| self.verify_reshare(i, commit, threshold))
+        {
+            return Err(Error::InvalidShare);
+        }
+        let xs: Vec<Fr> = commits.iter().map(|&(i, _)| into_fr_plus_1(i)).collect();
+        let mut coeff = vec![G1::zero(); threshold + 1];
+        for ((_, commit), l0) in commits.iter().zip(lagrange_coefficients(&xs)?) {
+            for (c, commit_c) in coeff.iter_mut().zip(&commit.coeff) {
+                let mut term = *commit_c;
+                term.mul_assign(l0);
+                c.add_assign(&term);
+            }
+        }
+        Ok(PublicKeySet::from(Commitment { coeff }))
+    }
+}
+
+impl SecretKeyShare {
+    /// Returns the `j`-th secret key share of the new key set, given the sub-shares and the
+    /// reshares' commitments by their dealers' indices. These must be the same reshares that were
+    /// used to compute the new `PublicKeySet`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::NotEnoughShares` if there are fewer than `threshold + 1` reshares of the
+    /// old key set, an `Error::DuplicateEntry` if an index appears twice, an
+    /// `Error::InvalidShare` if any reshare is invalid or doesn't match its commitment, or an
+    /// `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn from_reshares<'a, T, U, I>(
+        j: U,
+        pk_set: &PublicKeySet,
+        threshold: usize,
+        reshares: I,
+    ) -> Result<Self>
+    where
+        I: IntoIterator<Item = (T, &'a Commitment, &'a ReshareShare)>,
+        T: IntoFr,
+        U: IntoFr,
+    {
+        let reshares: Vec<_> = reshares.into_iter().collect();
+        if reshares.len() <= pk_set.threshold() {
+            return Err(Error::NotEnoughShares);
+        }
+        let is_valid = |&(i, commit, share): &(T, &Commitment, &ReshareShare)| {
+            pk_set.verify_reshare(i, commit, threshold) && share.verify(j, commit)
+        };
+        if !reshares.iter().all(is_valid) {
+            return Err(Error::InvalidShare);
+        }
+        let xs: Vec<Fr> = reshares
+            .iter()
+            .map(|&(i, _, _)| into_fr_plus_1(i))
+            .collect();
+        let mut fr = Fr::zero();
+        for ((_, _, share), l0) in reshares.iter().zip(lagrange_coefficients(&xs)?) {
+            let mut term = *(share.0).0;
+            term.mul_assign(&l0);
+            fr.add_assign(&term);
+        }
+        SecretKeyShare::from_mut_ptr(&mut fr as *mut Fr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use bincode;
+    use rand;
+
+    use super::super::SecretKeySet;
+    use super::*;
+
+    #[test]
+    fn test_reshare() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng).expect("Failed to create key set");
+        let pk_set = sk_set.public_keys();
+        let msg = "Signed by the old committee";
+        let ct = pk_set.public_key().encrypt(msg);
+
+        // Old holders 0, 2 and 3 reshare to a committee of five, with threshold 2.
+        let reshares: BTreeMap<_, _> = [0usize, 2, 3]
+            .iter()
+            .map(|&i| {
+                let share = sk_set.secret_key_share(i).expect("Failed to create share");
+                let reshare = Reshare::new(&share, 2, &mut rng).expect("Failed to reshare");
+                (i, reshare)
+            })
+            .collect();
+        let commits: BTreeMap<_, _> = reshares
+            .iter()
+            .map(|(i, reshare)| (*i, reshare.commitment()))
+            .collect();
+        let new_pk_set = pk_set.reshare(2, &commits).expect("Failed to reshare");
+        assert_eq!(2, new_pk_set.threshold());
+        assert_eq!(pk_set.public_key(), new_pk_set.public_key());
+
+        // Each new member receives their sub-shares, encrypted to their public key, and combines
+        // them.
+        let new_member_sks: Vec<SecretKey> = (0..5).map(|_| SecretKey::random()).collect();
+        let new_shares: Vec<_> = (0..5)
+            .map(|j| {
+                let sk = &new_member_sks[j];
+                let subs: BTreeMap<_, _> = reshares
+                    .iter()
+                    .map(|(i, reshare)| {
+                        let sub = reshare.share(j).expect("Failed to create share");
+                        let msg = bincode::serialize(&sub.encrypt(&sk.public_key()))
+                            .expect("serialize ciphertext");
+                        let ct: Ciphertext =
+                            bincode::deserialize(&msg).expect("deserialize ciphertext");
+                        let received =
+                            ReshareShare::decrypt(j, &commits[i], sk, &ct).expect("valid share");
+                        assert_eq!(sub, received);
+                        (*i, received)
+                    })
+                    .collect();
+                let items = commits.iter().map(|(i, commit)| (*i, commit, &subs[i]));
+                let share = SecretKeyShare::from_reshares(j, &pk_set, 2, items)
+                    .expect("Failed to combine sub-shares");
+                assert_eq!(new_pk_set.public_key_share(j), share.public_key_share());
+                share
+            })
+            .collect();
+
+        // Three new members sign and decrypt for the old public key.
+        let sig_shares: BTreeMap<_, _> = [0, 1, 4]
+            .iter()
+            .map(|&j| (j, new_shares[j].sign(msg)))
+            .collect();
+        let sig = new_pk_set
+            .combine_signatures(&sig_shares)
+            .expect("Failed to combine signatures");
+        assert!(pk_set.public_key().verify(&sig, msg));
+        let dec_shares: BTreeMap<_, _> = [1, 2, 3]
+            .iter()
+            .map(|&j| (j, new_shares[j].decrypt_share_no_verify(&ct)))
+            .collect();
+        let decrypted = new_pk_set
+            .decrypt(&dec_shares, &ct)
+            .expect("Failed to decrypt");
+        assert_eq!(msg.as_bytes(), &decrypted[..]);
+    }
+
+    #[test]
+    fn test_invalid_reshare() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng).expect("Failed to create key set");
+        let pk_set = sk_set.public_keys();
+        let share0 = sk_set.secret_key_share(0).expect("Failed to create share");
+        let share1 = sk_set.secret_key_share(1).expect("Failed to create share");
+        let commit0 = Reshare::new(&share0, 3, &mut rng)
+            .expect("Failed to reshare")
+            .commitment();
+        let reshare1 = Reshare::new(&share1, 3, &mut rng).expect("Failed to reshare");
+        let commit1 = reshare1.commitment();
+
+        // A single reshare is not enough.
+        let result = pk_set.reshare(3, vec![(0, &commit0)]);
+        assert_eq!(Err(Error::NotEnoughShares), result);
+
+        // A reshare under the wrong index, or with the wrong threshold, is rejected.
+        assert!(pk_set.verify_reshare(1, &commit1, 3));
+        assert!(!pk_set.verify_reshare(2, &commit1, 3));
+        assert!(!pk_set.verify_reshare(1, &commit1, 2));
+        let result = pk_set.reshare(3, vec![(0, &commit0), (2, &commit1)]);
+        assert_eq!(Err(Error::InvalidShare), result);
+
+        // A sub-share for a different member is rejected, and can only be decrypted by its
+        // recipient.
+        let sub = reshare1.share(4).expect("Failed to create share");
+        assert!(sub.verify(4, &commit1));
+        assert!(!sub.verify(5, &commit1));
+        let (sk4, sk5) = (SecretKey::random(), SecretKey::random());
+        let ct = sub.encrypt(&sk4.public_key());
+        let result = ReshareShare::decrypt(5, &commit1, &sk4, &ct);
+        assert_eq!(Err(Error::InvalidShare), result);
+        let result = ReshareShare::decrypt(4, &commit1, &sk5, &ct);
+        assert_eq!(Err(Error::InvalidShare), result);
+    }
+}
@@ -1,3 +1,104 @@
+use std::ops::Deref;
+
+use memsec::memzero;
+use serde::de::Error as DeserializeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::pedersen::PedersenShare;
+use super::{SecretKey, SecretKeyShare, SK_SIZE};
+
+/// A wrapper that makes a `SecretKey`, `SecretKeyShare` or `PedersenShare` serializable.
+///
+/// The secret types deliberately don't implement `Serialize` and `Deserialize` themselves, so that
+/// they can't be serialized by accident, e.g. as part of a larger structure. Wrapping them in a
+/// `SerdeSecret` makes that an explicit decision. When deserializing, the bytes are read into a
+/// buffer on the stack, which is overwritten with zeros once the value is in `mlock`ed memory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SerdeSecret<T>(pub T);
+
+impl<T> SerdeSecret<T> {
+    /// Returns the wrapped secret.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for SerdeSecret<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl Serialize for SerdeSecret<SecretKey> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        SerdeSecret(&self.0).serialize(s)
+    }
+}
+
+impl Serialize for SerdeSecret<&SecretKey> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let mut bytes = [0u8; SK_SIZE];
+        self.0.to_bytes(&mut bytes);
+        let result = bytes.serialize(s);
+        unsafe {
+            memzero(bytes.as_mut_ptr(), SK_SIZE);
+        }
+        result
+    }
+}
+
+impl Serialize for SerdeSecret<SecretKeyShare> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        SerdeSecret(&(self.0).0).serialize(s)
+    }
+}
+
+impl Serialize for SerdeSecret<&SecretKeyShare> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        SerdeSecret(&(self.0).0).serialize(s)
+    }
+}
+
+impl Serialize for SerdeSecret<PedersenShare> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        SerdeSecret(&self.0).serialize(s)
+    }
+}
+
+impl Serialize for SerdeSecret<&PedersenShare> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let value = SerdeSecret(self.0.secret_key_share());
+        let blinding = SerdeSecret(self.0.blinding());
+        (value, blinding).serialize(s)
+    }
+}
+
+impl<'de> Deserialize<'de> for SerdeSecret<SecretKey> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let mut bytes = <[u8; SK_SIZE]>::deserialize(d)?;
+        SecretKey::from_bytes(&mut bytes)
+            .map(SerdeSecret)
+            .map_err(D::Error::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for SerdeSecret<SecretKeyShare> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let SerdeSecret(sk) = SerdeSecret::<SecretKey>::deserialize(d)?;
+        Ok(SerdeSecret(SecretKeyShare(sk)))
+    }
+}
+
+impl<'de> Deserialize<'de> for SerdeSecret<PedersenShare> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let (SerdeSecret(value), SerdeSecret(blinding)) =
+            <(SerdeSecret<SecretKeyShare>, SerdeSecret<SecretKeyShare>)>::deserialize(d)?;
+        Ok(SerdeSecret(PedersenShare::new(value, blinding)))
+    }
+}
+
 /// Serialization and deserialization of a group element's compressed representation.
 pub mod projective {
     use pairing::{CurveAffine, CurveProjective, EncodedPoint};
@@ -81,6 +182,30 @@ pub mod projective_vec {
     }
 }
 
+/// Serialization and deserialization of a field element's big-endian representation.
+pub mod field {
+    use pairing::PrimeField;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::field_vec::FieldWrap;
+
+    pub fn serialize<S, F>(f: &F, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        F: PrimeField,
+    {
+        FieldWrap::<F, &F>::new(f).serialize(s)
+    }
+
+    pub fn deserialize<'de, D, F>(d: D) -> Result<F, D::Error>
+    where
+        D: Deserializer<'de>,
+        F: PrimeField,
+    {
+        Ok(<FieldWrap<F, F>>::deserialize(d)?.into_inner())
+    }
+}
+
 /// Serialization and deserialization of vectors of field elements.
 pub mod field_vec {
     use std::borrow::Borrow;
@@ -156,6 +281,9 @@ mod tests {
     use pairing::Engine;
     use rand::{self, Rng};
 
+    use super::super::{SecretKey, SecretKeyShare};
+    use super::SerdeSecret;
+
     #[derive(Debug, Serialize, Deserialize)]
     pub struct Vecs<E: Engine> {
         #[serde(with = "super::projective_vec")]
@@ -181,4 +309,23 @@ mod tests {
         let de_vecs = bincode::deserialize(&ser_vecs).expect("deserialize vecs");
         assert_eq!(vecs, de_vecs);
     }
+
+    #[test]
+    fn serde_secret() {
+        let sk: SecretKey = rand::random();
+        let ser_sk = bincode::serialize(&SerdeSecret(&sk)).expect("serialize secret key");
+        let de_sk: SerdeSecret<SecretKey> =
+            bincode::deserialize(&ser_sk).expect("deserialize secret key");
+        assert_eq!(sk, de_sk.into_inner());
+
+        let share: SecretKeyShare = rand::random();
+        let ser_share = bincode::serialize(&SerdeSecret(share.clone())).expect("serialize share");
+        let de_share: SerdeSecret<SecretKeyShare> =
+            bincode::deserialize(&ser_share).expect("deserialize share");
+        assert_eq!(share, *de_share);
+
+        // Bytes that don't represent a field element are rejected.
+        let invalid = vec![0xff; 32];
+        assert!(bincode::deserialize::<SerdeSecret<SecretKey>>(&invalid).is_err());
+    }
 }
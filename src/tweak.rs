@@ -0,0 +1,143 @@
+//! Child keys derived from a public key and a tag, without a new key generation.
+//!
+//! For a public key `pk = s * g` and a tag, the child public key is `pk + h * g`, where `h` is a
+//! hash of `pk` and the tag, and the child secret key is `s + h`. Anyone can compute child public
+//! keys from the parent public key, and they can't be linked to it without the tag.
+//!
+//! Since the shift is the same for every index, a `PublicKeySet` is tweaked by adding the constant
+//! `h * g` to its commitment, and each `SecretKeyShare` by adding `h`. The tweaked shares can then
+//! threshold-sign and decrypt for the child key.
+//!
+//! *WARNING* Tweaks are non-hardened: Anyone who knows a child secret key and its tag can compute
+//! the parent secret key. In the same way, a child share and its tag reveal the parent share.
+
+use pairing::bls12_381::{Fr, G1Affine};
+use pairing::{CurveAffine, CurveProjective, Field};
+
+use super::error::Result;
+use super::poly::Commitment;
+use super::{hash_fr, PublicKey, PublicKeySet, SecretKey, SecretKeyShare};
+
+/// The domain separation tag for the hash of a public key and a tag.
+const TWEAK_DST: &[u8] = b"threshold_crypto tweak";
+
+/// Returns the hash of the public key and the tag, by which the secret key is shifted.
+fn tweak_fr(pk: &PublicKey, tag: &[u8]) -> Fr {
+    let mut msg = TWEAK_DST.to_vec();
+    msg.extend(pk.to_bytes());
+    msg.extend(tag);
+    hash_fr(&msg)
+}
+
+impl PublicKey {
+    /// Returns the child public key with the given tag.
+    pub fn tweak<T: AsRef<[u8]>>(&self, tag: T) -> PublicKey {
+        let mut g = G1Affine::one().mul(tweak_fr(self, tag.as_ref()));
+        g.add_assign(&self.0);
+        PublicKey(g)
+    }
+}
+
+impl SecretKey {
+    /// Returns the child secret key with the given tag, which matches `public_key().tweak(tag)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn tweak<T: AsRef<[u8]>>(&self, tag: T) -> Result<SecretKey> {
+        let mut fr = tweak_fr(&self.public_key(), tag.as_ref());
+        fr.add_assign(&self.0);
+        SecretKey::from_mut_ptr(&mut fr as *mut Fr)
+    }
+}
+
+impl PublicKeySet {
+    /// Returns the child key set with the given tag. Its public key is `public_key().tweak(tag)`,
+    /// and each public key share is shifted by the same amount.
+    pub fn tweak<T: AsRef<[u8]>>(&self, tag: T) -> PublicKeySet {
+        let h = tweak_fr(&self.public_key(), tag.as_ref());
+        let shift = Commitment {
+            coeff: vec![G1Affine::one().mul(h)],
+        };
+        PublicKeySet::from(&self.commit + shift)
+    }
+}
+
+impl SecretKeyShare {
+    /// Returns the child share with the given tag, which matches the public key share of
+    /// `pk_set.tweak(tag)`. The key set must be the one this share belongs to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Error::MlockFailed` if we have reached the systems's locked memory limit.
+    pub fn tweak<T: AsRef<[u8]>>(&self, pk_set: &PublicKeySet, tag: T) -> Result<SecretKeyShare> {
+        let mut fr = tweak_fr(&pk_set.public_key(), tag.as_ref());
+        fr.add_assign(&(self.0).0);
+        SecretKeyShare::from_mut_ptr(&mut fr as *mut Fr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rand;
+
+    use super::super::SecretKeySet;
+    use super::*;
+
+    #[test]
+    fn test_tweak_key() {
+        let sk = SecretKey::random();
+        let pk = sk.public_key();
+        let child_sk = sk.tweak("account 1").expect("Failed to tweak");
+        assert_eq!(pk.tweak("account 1"), child_sk.public_key());
+        assert_ne!(pk, child_sk.public_key());
+        assert_ne!(pk.tweak("account 2"), child_sk.public_key());
+        let msg = "Signed by a child key";
+        assert!(pk.tweak("account 1").verify(&child_sk.sign(msg), msg));
+    }
+
+    #[test]
+    fn test_tweak_key_set() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng).expect("Failed to create key set");
+        let pk_set = sk_set.public_keys();
+        let child_pk_set = pk_set.tweak("account 1");
+        assert_eq!(2, child_pk_set.threshold());
+        assert_eq!(
+            pk_set.public_key().tweak("account 1"),
+            child_pk_set.public_key()
+        );
+
+        let child_shares: BTreeMap<_, _> = [0, 2, 5]
+            .iter()
+            .map(|&i| {
+                let share = sk_set.secret_key_share(i).expect("Failed to create share");
+                let child = share.tweak(&pk_set, "account 1").expect("Failed to tweak");
+                assert_eq!(child_pk_set.public_key_share(i), child.public_key_share());
+                (i, child)
+            })
+            .collect();
+
+        // The committee signs and decrypts for the child key.
+        let msg = "Signed by the committee for a child key";
+        let sig_shares: BTreeMap<_, _> = child_shares
+            .iter()
+            .map(|(i, share)| (*i, share.sign(msg)))
+            .collect();
+        let sig = child_pk_set
+            .combine_signatures(&sig_shares)
+            .expect("Failed to combine signatures");
+        assert!(child_pk_set.public_key().verify(&sig, msg));
+        let ct = child_pk_set.public_key().encrypt(msg);
+        let dec_shares: BTreeMap<_, _> = child_shares
+            .iter()
+            .map(|(i, share)| (*i, share.decrypt_share_no_verify(&ct)))
+            .collect();
+        let decrypted = child_pk_set
+            .decrypt(&dec_shares, &ct)
+            .expect("Failed to decrypt");
+        assert_eq!(msg.as_bytes(), &decrypted[..]);
+    }
+}
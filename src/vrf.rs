@@ -0,0 +1,236 @@
+//! A verifiable random function (VRF) based on BLS signatures.
+//!
+//! BLS signatures are _unique_: For a given key and message there is exactly one valid signature.
+//! So the signature of an input can serve as a proof, and a hash of it as a pseudorandom output
+//! that nobody can predict without the secret key, but everyone can verify given the public key.
+//!
+//! The same works with threshold keys: Any `threshold + 1` signature shares combine into the same
+//! signature, so the output is unknown until enough key share holders have revealed their shares.
+//!
+//! Inputs are hashed with the IETF hash-to-curve function and the VRF's own domain separation tag,
+//! so signing any message doesn't reveal a VRF output. However, signing an arbitrary element of
+//! `G2` does: The key set must not be used for blind signatures, or with `sign_g2` on points chosen
+//! by others.
+
+use std::fmt;
+
+use byteorder::{BigEndian, ByteOrder};
+use pairing::bls12_381::G2;
+use tiny_keccak::sha3_256;
+
+use super::{
+    hash_to_g2, HexBytes, IntoFr, PublicKey, PublicKeySet, PublicKeyShare, Result, SecretKey,
+    SecretKeyShare, Signature, SignatureShare,
+};
+
+/// The length of a VRF output in bytes.
+pub const VRF_OUTPUT_SIZE: usize = 32;
+
+/// The domain separation tag for hashing VRF inputs to `G2`.
+const INPUT_DST: &[u8] = b"THRESHOLD_CRYPTO_VRF_BLS12381G2_XMD:SHA-256_SSWU_RO_";
+
+/// The domain separation tag for hashing VRF proofs to outputs.
+const OUTPUT_DST: &[u8] = b"threshold_crypto VRF output";
+
+/// The pseudorandom output of the VRF.
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VrfOutput([u8; VRF_OUTPUT_SIZE]);
+
+impl fmt::Debug for VrfOutput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "VrfOutput({:?})", HexBytes(&self.0))
+    }
+}
+
+impl AsRef<[u8]> for VrfOutput {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl VrfOutput {
+    /// Returns the output that belongs to the given proof.
+    fn from_proof(proof: &VrfProof) -> Self {
+        let mut msg = OUTPUT_DST.to_vec();
+        msg.extend(proof.0.to_bytes());
+        VrfOutput(sha3_256(&msg))
+    }
+
+    /// Returns the output bytes.
+    pub fn as_bytes(&self) -> &[u8; VRF_OUTPUT_SIZE] {
+        &self.0
+    }
+
+    /// Returns the first eight bytes of the output, as a big-endian integer.
+    pub fn to_u64(&self) -> u64 {
+        BigEndian::read_u64(&self.0[..8])
+    }
+
+    /// Returns `true` with probability `weight / total`, if the output is uniformly random.
+    ///
+    /// This can be used for stake-weighted sortition: A participant with `weight` of the `total`
+    /// stake is selected if their output for the current round satisfies this.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `total` is zero.
+    pub fn is_below(&self, weight: u64, total: u64) -> bool {
+        assert_ne!(0, total, "total weight must be positive");
+        // Compare `self.to_u64() / 2^64 < weight / total`.
+        u128::from(self.to_u64()) * u128::from(total) < u128::from(weight) << 64
+    }
+}
+
+/// A proof that a `VrfOutput` was computed correctly. This is the signature of the input.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct VrfProof(pub Signature);
+
+impl VrfProof {
+    /// Returns the output that belongs to this proof. The proof is _not_ verified.
+    pub fn output(&self) -> VrfOutput {
+        VrfOutput::from_proof(self)
+    }
+}
+
+impl SecretKey {
+    /// Evaluates the VRF on the given input, and returns the output together with its proof.
+    pub fn vrf_prove<M: AsRef<[u8]>>(&self, input: M) -> (VrfOutput, VrfProof) {
+        let proof = VrfProof(self.sign_g2(hash_vrf_input(input)));
+        (proof.output(), proof)
+    }
+}
+
+impl PublicKey {
+    /// Returns the VRF output for the given input if the proof is valid, or `None` otherwise.
+    pub fn vrf_verify<M: AsRef<[u8]>>(&self, input: M, proof: &VrfProof) -> Option<VrfOutput> {
+        if self.verify_g2(&proof.0, hash_vrf_input(input)) {
+            Some(proof.output())
+        } else {
+            None
+        }
+    }
+}
+
+impl SecretKeyShare {
+    /// Returns a share of the VRF proof for the given input. Any `threshold + 1` of them can be
+    /// combined using `PublicKeySet::combine_vrf_proofs`.
+    pub fn vrf_prove_share<M: AsRef<[u8]>>(&self, input: M) -> SignatureShare {
+        self.sign_g2(hash_vrf_input(input))
+    }
+}
+
+impl PublicKeyShare {
+    /// Returns `true` if the VRF proof share is valid for the given input.
+    pub fn vrf_verify_share<M: AsRef<[u8]>>(&self, input: M, share: &SignatureShare) -> bool {
+        self.verify_g2(share, hash_vrf_input(input))
+    }
+}
+
+impl PublicKeySet {
+    /// Combines the VRF proof shares into the VRF output and proof for the main public key.
+    ///
+    /// The shares are not verified: Use `PublicKeyShare::vrf_verify_share` to check them first.
+    pub fn combine_vrf_proofs<'a, T, I>(&self, shares: I) -> Result<(VrfOutput, VrfProof)>
+    where
+        I: IntoIterator<Item = (T, &'a SignatureShare)>,
+        T: IntoFr,
+    {
+        let proof = VrfProof(self.combine_signatures(shares)?);
+        Ok((proof.output(), proof))
+    }
+}
+
+/// Returns the hash in `G2` that is signed to evaluate the VRF on the given input.
+fn hash_vrf_input<M: AsRef<[u8]>>(input: M) -> G2 {
+    hash_to_g2(input, INPUT_DST)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rand::{self, random};
+
+    use super::super::SecretKeySet;
+    use super::*;
+
+    #[test]
+    fn test_simple_vrf() {
+        let sk0: SecretKey = random();
+        let sk1: SecretKey = random();
+        let pk0 = sk0.public_key();
+        let input = b"Round 42";
+
+        let (output, proof) = sk0.vrf_prove(input);
+        assert_eq!(Some(output), pk0.vrf_verify(input, &proof));
+        assert_eq!((output, proof.clone()), sk0.vrf_prove(input)); // Deterministic.
+        assert_eq!(None, pk0.vrf_verify(b"Round 43", &proof)); // Wrong input.
+        let (output1, proof1) = sk1.vrf_prove(input);
+        assert_eq!(None, pk0.vrf_verify(input, &proof1)); // Wrong key.
+        assert_ne!(output, output1);
+
+        // The VRF proof is not a valid signature of the input itself, with or without the domain
+        // separation tag, so signing doesn't reveal VRF outputs.
+        assert!(!pk0.verify(&proof.0, input));
+        let mut msg = INPUT_DST.to_vec();
+        msg.extend(&input[..]);
+        assert_ne!(proof.0, sk0.sign(&msg));
+        assert_ne!(proof.0, sk0.sign(input));
+    }
+
+    #[test]
+    fn test_threshold_vrf() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng).expect("Failed to create `SecretKeySet`");
+        let pk_set = sk_set.public_keys();
+        let input = b"Leader election, epoch 7";
+
+        let proof_shares = |indices: &[usize]| -> BTreeMap<_, _> {
+            indices
+                .iter()
+                .map(|&i| {
+                    let sk_share = sk_set
+                        .secret_key_share(i)
+                        .unwrap_or_else(|_| panic!("Failed to create `SecretKeyShare` #{}", i));
+                    (i, sk_share.vrf_prove_share(input))
+                })
+                .collect()
+        };
+
+        let shares = proof_shares(&[1, 4, 6]);
+        for (i, share) in &shares {
+            assert!(pk_set.public_key_share(*i).vrf_verify_share(input, share));
+            assert!(!pk_set
+                .public_key_share(*i)
+                .vrf_verify_share(b"other", share));
+        }
+        let (output, proof) = pk_set.combine_vrf_proofs(&shares).expect("shares match");
+        assert_eq!(Some(output), pk_set.public_key().vrf_verify(input, &proof));
+
+        // A different set of key share holders computes the same output.
+        let (output2, _) = pk_set
+            .combine_vrf_proofs(&proof_shares(&[0, 2, 3]))
+            .expect("shares match");
+        assert_eq!(output, output2);
+
+        // Signature shares of the input don't match the proof shares.
+        let sk_share = sk_set.secret_key_share(1).expect("Failed to create share");
+        let mut msg = INPUT_DST.to_vec();
+        msg.extend(&input[..]);
+        assert_ne!(shares[&1], sk_share.sign(&msg));
+    }
+
+    #[test]
+    fn test_is_below() {
+        let output = |first_byte| {
+            let mut bytes = [0; VRF_OUTPUT_SIZE];
+            bytes[0] = first_byte;
+            VrfOutput(bytes)
+        };
+        assert!(output(0x00).is_below(1, 2));
+        assert!(output(0x7f).is_below(1, 2));
+        assert!(!output(0x80).is_below(1, 2));
+        assert!(!output(0x00).is_below(0, 5));
+        assert!(output(0xff).is_below(5, 5));
+    }
+}